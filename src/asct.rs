@@ -1,6 +1,6 @@
 use chrono::NaiveDate;
 use regex::Regex;
-use crate::ParseError;
+use crate::{ParseError, ParseErrorKind};
 use chrono::Utc;
 use chrono::DateTime;
 
@@ -13,6 +13,8 @@ const DATE_FORMAT_ASCT: &str= "(Mon|Tue|Wed|Thu|Fri|Sat|Sun) \
 /// 
 /// For example,  `Sun Nov 6 08:49:37 1994` dates.
 /// 
+/// This is a strict parser, see [parse_cookie_date](crate::parse_cookie_date) for the user agent algorithm.
+/// 
 pub fn parse_asct_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(DATE_FORMAT_ASCT).unwrap();
@@ -34,7 +36,7 @@ pub fn parse_asct_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
             "Oct" => 10,
             "Nov" => 11,
             "Dec" => 12,
-            _ => return Err(ParseError::new("Invalid date"))
+            _ => return Err(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))
         };
 
         let day : u32 = captures.get(3).unwrap().as_str().parse().unwrap();
@@ -46,14 +48,14 @@ pub fn parse_asct_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
         let year: i32 = captures.get(7).unwrap().as_str().parse().unwrap();
        
         let naive = NaiveDate::from_ymd_opt(year, month, day)
-            .ok_or(ParseError::new("Invalid date"))?
+            .ok_or(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))?
             .and_hms_opt(hour,min,secs)
-            .ok_or(ParseError::new("Invalid date"))?;
+            .ok_or(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))?;
 
         return Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
 
     } else {
-        return Err(ParseError::new("Invalid date"));
+        return Err(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"));
     }
 }
 
@@ -0,0 +1,77 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Source of the current time, used by a [CookieJar](crate::CookieJar) to set creation and access times
+/// and to check if cookies are expired.
+///
+/// The jar uses [SystemClock] by default. Tests and tools that rebuild a saved jar can use a [ManualClock]
+/// instead, see [CookieJar::set_clock](crate::CookieJar::set_clock).
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Gets the current time
+    fn now(&self) -> SystemTime;
+}
+
+/// Clock that gets the operating system time
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock whose time only changes when it is set or advanced
+///
+/// ```rust
+/// use wcookie::{Clock, ManualClock};
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let clock = ManualClock::new(UNIX_EPOCH);
+/// clock.advance(Duration::from_secs(60));
+///
+/// assert_eq!(clock.now(), UNIX_EPOCH + Duration::from_secs(60));
+/// ```
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<SystemTime>
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at `now`
+    pub fn new(now: SystemTime) -> ManualClock {
+        ManualClock { now: Mutex::new(now) }
+    }
+
+    /// Sets the current time
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the current time forward
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::str::FromStr;
+use crate::{CookiePolicy, ParseError, PublicSuffixList, RequestUrl};
+
+/// HTTP methods considered safe by [RFC7231 Section 4.2.1](https://datatracker.ietf.org/doc/html/rfc7231#section-4.2.1)
+const SAFE_METHODS: [&str; 4] = ["GET", "HEAD", "OPTIONS", "TRACE"];
+
+/// Kind of request, as used by `SameSite` enforcement
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RequestKind {
+    /// Top-level navigation, the request URL is loaded at the browser window
+    Navigation,
+    /// Any other request: images, scripts, `fetch` calls, iframes...
+    Subresource
+}
+
+/// Context of a request used to decide which cookies are sent, following
+/// [RFC6265bis Section 5.2](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.2)
+/// same-site and cross-site requests.
+///
+/// A request is same-site when its URL has the same site (registrable domain, see [registrable_domain]) than
+/// the top-level site, `site_for_cookies`, and than the `initiator` of the request, if any. Otherwise it is cross-site.
+///
+/// ```rust
+/// use wcookie::{RequestContext, RequestKind, RequestUrl};
+/// use std::str::FromStr;
+///
+/// // User typed the URL at the browser
+/// let context = RequestContext::new("https://www.example.com/").unwrap();
+/// assert!(context.is_same_site());
+///
+/// // Image of example.com embedded in a page of other.org
+/// let mut context = RequestContext::new("https://www.example.com/logo.png").unwrap();
+/// context.site_for_cookies = Some(RequestUrl::from_str("https://other.org/").unwrap());
+/// context.kind = RequestKind::Subresource;
+/// assert!(!context.is_same_site());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// Request URL
+    pub url: RequestUrl,
+    /// URL of the top-level document, the site for cookies. `None` if there is not a top-level site, for example,
+    /// at sandboxed documents, then the request is cross-site.
+    pub site_for_cookies: Option<RequestUrl>,
+    /// URL of the document that initiated the request, if any
+    pub initiator: Option<RequestUrl>,
+    /// Navigation or subresource request
+    pub kind: RequestKind,
+    /// HTTP method in uppercase
+    pub method: String
+}
+
+impl RequestContext {
+    /// Creates the context of a top-level `GET` navigation to `url` not initiated by any document, for example,
+    /// when the user types the URL. The request is same-site.
+    pub fn new(url: &str) -> Result<RequestContext, ParseError> {
+        let url = RequestUrl::from_str(url)?;
+        return Ok(RequestContext {
+            site_for_cookies: Some(url.clone()),
+            url,
+            initiator: None,
+            kind: RequestKind::Navigation,
+            method: String::from("GET")
+        });
+    }
+
+    /// Checks if the request is same-site, using the bundled [PublicSuffixList]
+    pub fn is_same_site(&self) -> bool {
+        return self.is_same_site_with_list(&PublicSuffixList::bundled());
+    }
+
+    /// Checks if the request is same-site, getting sites with `public_suffix_list`
+    pub fn is_same_site_with_list(&self, public_suffix_list: &PublicSuffixList) -> bool {
+        let site = |url| site(url, public_suffix_list);
+        let request_site = site(&self.url);
+
+        let top_level = match self.site_for_cookies {
+            Some(ref top_level) => top_level,
+            None => return false
+        };
+
+        if request_site != site(top_level) {
+            return false;
+        }
+
+        return match self.initiator {
+            Some(ref initiator) => request_site == site(initiator),
+            None => true
+        };
+    }
+
+    /// Checks if the request is same-site and it has the same scheme than the top-level site and the initiator,
+    /// as used by schemeful same-site, see [CookiePolicy::schemeful_same_site].
+    /// WebSocket schemes are compared as `http` and `https`.
+    ///
+    /// ```rust
+    /// use wcookie::{RequestContext, RequestUrl};
+    /// use std::str::FromStr;
+    ///
+    /// let mut context = RequestContext::new("https://www.example.com/").unwrap();
+    /// context.site_for_cookies = Some(RequestUrl::from_str("http://example.com/").unwrap());
+    ///
+    /// assert!(context.is_same_site());
+    /// assert!(!context.is_schemeful_same_site());
+    /// ```
+    pub fn is_schemeful_same_site(&self) -> bool {
+        return self.is_schemeful_same_site_with_list(&PublicSuffixList::bundled());
+    }
+
+    /// Checks if the request is schemeful same-site, see [RequestContext::is_schemeful_same_site], getting sites
+    /// with `public_suffix_list`
+    pub fn is_schemeful_same_site_with_list(&self, public_suffix_list: &PublicSuffixList) -> bool {
+        if !self.is_same_site_with_list(public_suffix_list) {
+            return false;
+        }
+
+        let scheme = self.url.http_scheme();
+        return self.site_for_cookies.iter().chain(self.initiator.iter())
+            .all(|url| url.http_scheme() == scheme);
+    }
+
+    /// Checks if the request is same-site, using schemeful same-site if `policy` enables it
+    pub(crate) fn is_same_site_with_policy(&self, policy: &CookiePolicy, public_suffix_list: &PublicSuffixList) -> bool {
+        if policy.schemeful_same_site {
+            return self.is_schemeful_same_site_with_list(public_suffix_list);
+        }
+        return self.is_same_site_with_list(public_suffix_list);
+    }
+
+    /// Gets the key of the cookie partition used by the request: the schemeful site of the top-level document,
+    /// `site_for_cookies`, or of the request URL if there is not a top-level site, for example `https://example.com`.
+    /// So `http://example.com` and `https://example.com` have separate partitions, as in
+    /// [CHIPS](https://datatracker.ietf.org/doc/html/draft-cutler-httpbis-partitioned-cookies#section-2.1).
+    /// See [CookieJar::set_partitioned_storage](crate::CookieJar::set_partitioned_storage).
+    pub fn partition_key(&self) -> String {
+        return self.partition_key_with_list(&PublicSuffixList::bundled());
+    }
+
+    /// Gets the key of the cookie partition used by the request, see [RequestContext::partition_key], getting
+    /// sites with `public_suffix_list`
+    pub fn partition_key_with_list(&self, public_suffix_list: &PublicSuffixList) -> String {
+        let top_level = self.site_for_cookies.as_ref().unwrap_or(&self.url);
+        return format!("{}://{}", top_level.http_scheme(), site(top_level, public_suffix_list));
+    }
+
+    /// Checks if the request is a top-level navigation
+    pub fn is_navigation(&self) -> bool {
+        self.kind == RequestKind::Navigation
+    }
+
+    /// Checks if the request method is safe: `GET`, `HEAD`, `OPTIONS` or `TRACE`
+    pub fn is_safe_method(&self) -> bool {
+        SAFE_METHODS.iter().any(|method| method.eq_ignore_ascii_case(&self.method))
+    }
+}
+
+/// Gets the site of an URL: its registrable domain, or the host for IP addresses and public suffixes
+pub(crate) fn site<'a>(url: &'a RequestUrl, public_suffix_list: &PublicSuffixList) -> &'a str {
+    public_suffix_list.registrable_domain(&url.host).unwrap_or(&url.host)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{RequestContext, RequestKind, RequestUrl};
+
+    fn context(url: &str, site_for_cookies: Option<&str>, initiator: Option<&str>) -> RequestContext {
+        let mut context = RequestContext::new(url).unwrap();
+        context.site_for_cookies = site_for_cookies.map(|u| RequestUrl::from_str(u).unwrap());
+        context.initiator = initiator.map(|u| RequestUrl::from_str(u).unwrap());
+        context
+    }
+
+    #[test]
+    fn test_same_site() {
+        assert!(context("https://www.example.com/", Some("https://example.com/"), None).is_same_site());
+        assert!(context("https://a.example.co.uk/", Some("https://b.example.co.uk/"), Some("https://example.co.uk")).is_same_site());
+        assert!(context("http://127.0.0.1/", Some("http://127.0.0.1:8080/"), None).is_same_site());
+    }
+
+    #[test]
+    fn test_cross_site() {
+        assert!(!context("https://www.example.com/", Some("https://evil.org/"), None).is_same_site());
+        assert!(!context("https://www.example.com/", None, None).is_same_site());
+        assert!(!context("https://www.example.com/", Some("https://example.com/"), Some("https://evil.org/")).is_same_site());
+        assert!(!context("https://a.github.io/", Some("https://b.github.io/"), None).is_same_site());
+        assert!(!context("https://example.com.evil.org/", Some("https://example.com/"), None).is_same_site());
+    }
+
+    #[test]
+    fn test_schemeful_same_site() {
+        assert!(context("https://www.example.com/", Some("https://example.com/"), Some("https://example.com/")).is_schemeful_same_site());
+        assert!(context("wss://www.example.com/", Some("https://example.com/"), None).is_schemeful_same_site());
+        assert!(!context("http://www.example.com/", Some("https://example.com/"), None).is_schemeful_same_site());
+        assert!(!context("https://www.example.com/", Some("https://example.com/"), Some("http://example.com/")).is_schemeful_same_site());
+        assert!(!context("https://www.example.com/", Some("https://evil.org/"), None).is_schemeful_same_site());
+    }
+
+    #[test]
+    fn test_partition_key() {
+        assert_eq!(context("https://widget.org/", Some("https://www.example.com/"), None).partition_key(), "https://example.com");
+        assert_eq!(context("https://widget.org/", Some("http://www.example.com/"), None).partition_key(), "http://example.com");
+        assert_eq!(context("wss://widget.org/", None, None).partition_key(), "https://widget.org");
+    }
+
+    #[test]
+    fn test_safe_method() {
+        let mut context = RequestContext::new("https://www.example.com/").unwrap();
+
+        assert!(context.is_safe_method());
+        assert!(context.is_navigation());
+
+        context.method = String::from("post");
+        assert!(!context.is_safe_method());
+
+        context.method = String::from("head");
+        context.kind = RequestKind::Subresource;
+        assert!(context.is_safe_method());
+        assert!(!context.is_navigation());
+    }
+}
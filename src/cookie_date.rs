@@ -0,0 +1,183 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::{ParseError, ParseErrorKind};
+
+/// Month names, matched by their first three characters
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// Checks if `c` is a `delimiter` of the cookie-date grammar
+fn is_delimiter(c: u8) -> bool {
+    c == 0x09 || (0x20..=0x2F).contains(&c) || (0x3B..=0x40).contains(&c)
+        || (0x5B..=0x60).contains(&c) || (0x7B..=0x7E).contains(&c)
+}
+
+/// Parses `min` to `max` digits at the start of `token`. The digits must be followed by
+/// the end of the token or by a non-digit. Returns the number and the rest of the token.
+fn leading_number(token: &[u8], min: usize, max: usize) -> Option<(u32, &[u8])> {
+    let digits = token.iter().take_while(|c| c.is_ascii_digit()).count();
+    if digits < min || digits > max {
+        return None;
+    }
+    let number = token[0..digits].iter().fold(0, |n, c| n * 10 + u32::from(c - b'0'));
+    return Some((number, &token[digits..]));
+}
+
+/// Matches the `hms-time` production: `time-field ":" time-field ":" time-field`,
+/// where `time-field` has got 1 or 2 digits, optionally followed by non-digits
+fn parse_time(token: &[u8]) -> Option<(u32, u32, u32)> {
+    let (hour, rest) = leading_number(token, 1, 2)?;
+    let rest = rest.strip_prefix(b":")?;
+    let (minute, rest) = leading_number(rest, 1, 2)?;
+    let rest = rest.strip_prefix(b":")?;
+    let (second, _) = leading_number(rest, 1, 2)?;
+    return Some((hour, minute, second));
+}
+
+/// Matches the `month` production: a month name, case-insensitive, followed by any character
+fn parse_month(token: &[u8]) -> Option<u32> {
+    let name = token.get(0..3)?;
+    return MONTHS.iter()
+        .position(|month| name.eq_ignore_ascii_case(month.as_bytes()))
+        .map(|index| index as u32 + 1);
+}
+
+/// Parses a cookie date with the [RFC6265 Section 5.1.1](https://datatracker.ietf.org/doc/html/rfc6265#section-5.1.1)
+/// algorithm, as user agents do with the `Expires` attribute.
+///
+/// The date is split in tokens by delimiters, and the first tokens that look like a time, a day of the month,
+/// a month and a year are used, in any order. The day of the week and the time zone are ignored, the date is
+/// always in UTC. Two digit years are 1970 to 2069.
+///
+/// ```rust
+/// use wcookie::parse_cookie_date;
+/// use chrono::{TimeZone, Utc};
+///
+/// let expected = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+///
+/// assert_eq!(parse_cookie_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap(), expected);
+/// assert_eq!(parse_cookie_date("Wed, 21-Oct-2015 07:28:00 GMT").unwrap(), expected);
+/// assert_eq!(parse_cookie_date("21 Oct 15 07:28:00").unwrap(), expected);
+/// assert_eq!(parse_cookie_date("Wed Oct 21 07:28:00 2015").unwrap(), expected);
+/// ```
+///
+/// The date is rejected if any component is missing or out of range, for example, years before 1601
+/// or `23:59:60`, as seconds must be lower than 60.
+pub fn parse_cookie_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+
+    for token in date.as_bytes().split(|c| is_delimiter(*c)).filter(|t| !t.is_empty()) {
+        if time.is_none() {
+            if let Some(hms) = parse_time(token) {
+                time = Some(hms);
+                continue;
+            }
+        }
+        if day.is_none() {
+            if let Some((value, _)) = leading_number(token, 1, 2) {
+                day = Some(value);
+                continue;
+            }
+        }
+        if month.is_none() {
+            if let Some(value) = parse_month(token) {
+                month = Some(value);
+                continue;
+            }
+        }
+        if year.is_none() {
+            if let Some((value, _)) = leading_number(token, 2, 4) {
+                year = Some(value);
+                continue;
+            }
+        }
+    }
+
+    let error = || ParseError::new(ParseErrorKind::InvalidDate, format!("Invalid cookie date: {}", date));
+
+    let (hour, minute, second) = time.ok_or_else(error)?;
+    let day = day.ok_or_else(error)?;
+    let month = month.ok_or_else(error)?;
+    let mut year = year.ok_or_else(error)?;
+
+    // Fix millenium, for 2 digit year
+    year += if year < 70 { 2000 } else if year < 100 { 1900 } else { 0 };
+
+    if !(1..=31).contains(&day) || year < 1601 || hour > 23 || minute > 59 || second > 59 {
+        return Err(error());
+    }
+
+    let naive = NaiveDate::from_ymd_opt(year as i32, month, day)
+        .and_then(|date| date.and_hms_opt(hour, minute, second))
+        .ok_or_else(error)?;
+
+    return Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+    use crate::parse_cookie_date;
+
+    fn date(year: i32, month: u32, day: u32, hour: u32, min: u32, sec: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, min, sec).unwrap()
+    }
+
+    #[test]
+    fn test_cookie_date_formats() {
+        let expected = date(2015, 10, 21, 7, 28, 0);
+
+        assert_eq!(parse_cookie_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap(), expected);
+        assert_eq!(parse_cookie_date("Wednesday, 21-Oct-15 07:28:00 GMT").unwrap(), expected);
+        assert_eq!(parse_cookie_date("Wed Oct 21 07:28:00 2015").unwrap(), expected);
+        assert_eq!(parse_cookie_date("Wed, 21-Oct-2015 07:28:00 GMT").unwrap(), expected);
+        assert_eq!(parse_cookie_date("21 Oct 2015 07:28:00").unwrap(), expected);
+        assert_eq!(parse_cookie_date("21 october 2015 7:28:0").unwrap(), expected);
+        assert_eq!(parse_cookie_date("2015 OCT 21 07:28:00 +0200").unwrap(), expected);
+        assert_eq!(parse_cookie_date("  Wed,21/Oct/2015;07:28:00 UTC  ").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_cookie_date_years() {
+        assert_eq!(parse_cookie_date("1 Jan 70 00:00:00").unwrap(), date(1970, 1, 1, 0, 0, 0));
+        assert_eq!(parse_cookie_date("1 Jan 99 00:00:00").unwrap(), date(1999, 1, 1, 0, 0, 0));
+        assert_eq!(parse_cookie_date("1 Jan 69 00:00:00").unwrap(), date(2069, 1, 1, 0, 0, 0));
+        assert_eq!(parse_cookie_date("1 Jan 00 00:00:00").unwrap(), date(2000, 1, 1, 0, 0, 0));
+        assert_eq!(parse_cookie_date("1 Jan 1601 00:00:00").unwrap(), date(1601, 1, 1, 0, 0, 0));
+        assert_eq!(parse_cookie_date("31 Dec 9999 23:59:59").unwrap(), date(9999, 12, 31, 23, 59, 59));
+        assert!(parse_cookie_date("31 Dec 1600 23:59:59").is_err());
+        assert!(parse_cookie_date("1 Jan 100 00:00:00").is_err());
+    }
+
+    #[test]
+    fn test_cookie_date_wrong() {
+        assert!(parse_cookie_date("").is_err());
+        assert!(parse_cookie_date("Wed, 21 Oct 2015").is_err());
+        assert!(parse_cookie_date("Wed, 21 2015 07:28:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, Oct 2015 07:28:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 21 Oct 07:28:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 32 Oct 2015 07:28:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 00 Oct 2015 07:28:00 GMT").is_err());
+        assert!(parse_cookie_date("Sat, 30 Feb 2015 07:28:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 21 Oct 2015 24:00:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 21 Oct 2015 23:60:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 21 Oct 2015 23:59:60 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 21 Oct 2015 007:28:00 GMT").is_err());
+        assert!(parse_cookie_date("Wed, 21 Oct 20155 07:28:00 GMT").is_err());
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+use crate::{Cookie, ParseError};
+
+/// Represents the value of a `Cookie` request header, as defined in
+/// [RFC6265 Section 5.4](https://datatracker.ietf.org/doc/html/rfc6265#section-5.4).
+///
+/// A `Cookie` header carries a list of `name=value` pairs separated by `"; "`. The order is kept
+/// and duplicated names are allowed, as user agents send several cookies with the same name when
+/// they have different domains or paths:
+///
+/// ```rust
+/// use wcookie::CookieHeader;
+/// use std::str::FromStr;
+///
+/// let header = CookieHeader::from_str("a=1; b=2; a=3").unwrap();
+///
+/// assert_eq!(header.len(), 3);
+/// assert_eq!(header.get("a").unwrap().value, "1");
+///
+/// let values: Vec<&str> = header.get_all("a").map(|c| c.value.as_str()).collect();
+/// assert_eq!(values, vec!["1", "3"]);
+///
+/// assert_eq!(header.to_string(), "a=1; b=2; a=3");
+/// ```
+///
+/// Parsing is lenient with the quirks found at real user agents: spaces around names and values,
+/// empty segments such as `a=1;; b=2;` and segments without `=`, which are read as a cookie with an
+/// empty name. Values may be empty. Parsing does not fail. Nameless cookies are written back as they
+/// were read, so `=foo` and `foo` are kept apart.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CookieHeader {
+    cookies: Vec<Cookie>,
+    /// For each cookie, whether it is written as a bare value, without `=`
+    bare: Vec<bool>
+}
+
+impl CookieHeader {
+    /// Creates an empty `Cookie` header
+    pub fn new() -> CookieHeader {
+        CookieHeader {
+            cookies: Vec::new(),
+            bare: Vec::new()
+        }
+    }
+
+    /// Appends a cookie at the end of the header. A cookie with an empty name is written as its bare value.
+    pub fn push(&mut self, cookie: Cookie) {
+        let bare = cookie.name.is_empty();
+        self.push_cookie(cookie, bare);
+    }
+
+    fn push_cookie(&mut self, cookie: Cookie, bare: bool) {
+        self.cookies.push(cookie);
+        self.bare.push(bare);
+    }
+
+    /// Gets the first cookie with `name`
+    pub fn get(&self, name: &str) -> Option<&Cookie> {
+        self.cookies.iter().find(|cookie| cookie.name == name)
+    }
+
+    /// Gets all the cookies with `name`, in header order
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Cookie> + 'a {
+        self.cookies.iter().filter(move |cookie| cookie.name == name)
+    }
+
+    /// Iterates over the cookies, in header order
+    pub fn iter(&self) -> std::slice::Iter<'_, Cookie> {
+        self.cookies.iter()
+    }
+
+    /// Number of cookies
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Checks if the header has not got cookies
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+impl FromStr for CookieHeader {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut header = CookieHeader::new();
+
+        for segment in s.split(';') {
+            let segment = segment.trim();
+            if segment.is_empty() {
+                continue;
+            }
+            let (cookie, bare) = match segment.find('=') {
+                Some(index) => (Cookie::new(segment[0..index].trim(), segment[index + 1..].trim()), false),
+                None => (Cookie::new("", segment), true)
+            };
+            if cookie.name.is_empty() && cookie.value.is_empty() {
+                continue;
+            }
+            header.push_cookie(cookie, bare);
+        }
+
+        return Ok(header);
+    }
+}
+
+impl fmt::Display for CookieHeader {
+    /// Writes the cookies separated by `"; "`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, cookie) in self.cookies.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            if self.bare[index] {
+                write!(f, "{}", cookie.value)?;
+            } else {
+                write!(f, "{}", cookie)?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+impl From<Vec<Cookie>> for CookieHeader {
+    fn from(cookies: Vec<Cookie>) -> Self {
+        let bare = cookies.iter().map(|cookie| cookie.name.is_empty()).collect();
+        CookieHeader {
+            cookies,
+            bare
+        }
+    }
+}
+
+impl IntoIterator for CookieHeader {
+    type Item = Cookie;
+    type IntoIter = std::vec::IntoIter<Cookie>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cookies.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CookieHeader {
+    type Item = &'a Cookie;
+    type IntoIter = std::slice::Iter<'a, Cookie>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cookies.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::{Cookie, CookieHeader};
+
+    #[test]
+    fn test_header1() {
+        let header = CookieHeader::from_str("a=1").unwrap();
+
+        assert_eq!(header.len(), 1);
+        assert_eq!(header.get("a"), Some(&Cookie::new("a", "1")));
+        assert!(header.get("b").is_none());
+    }
+
+    #[test]
+    fn test_header2() {
+        let header = CookieHeader::from_str("a=1; b=2; a=3").unwrap();
+
+        let cookies: Vec<&Cookie> = header.iter().collect();
+        assert_eq!(cookies, vec![&Cookie::new("a", "1"), &Cookie::new("b", "2"), &Cookie::new("a", "3")]);
+        assert_eq!(header.get_all("a").count(), 2);
+        assert_eq!(header.get_all("c").count(), 0);
+    }
+
+    #[test]
+    fn test_header_whitespace() {
+        let header = CookieHeader::from_str("  a = 1 ;b=2;   c=3  ").unwrap();
+
+        assert_eq!(header.to_string(), "a=1; b=2; c=3");
+    }
+
+    #[test]
+    fn test_header_empty_segments() {
+        let header = CookieHeader::from_str(";a=1;; ;b=2;").unwrap();
+
+        assert_eq!(header.len(), 2);
+        assert_eq!(header.to_string(), "a=1; b=2");
+    }
+
+    #[test]
+    fn test_header_empty_values() {
+        let header = CookieHeader::from_str("a=; b=x=y; novalue").unwrap();
+
+        assert_eq!(header.get("a").unwrap().value, "");
+        assert_eq!(header.get("b").unwrap().value, "x=y");
+        assert_eq!(header.get("").unwrap().value, "novalue");
+        assert_eq!(header.to_string(), "a=; b=x=y; novalue");
+    }
+
+    #[test]
+    fn test_header_empty_names() {
+        let header = CookieHeader::from_str("=foo; bar; = baz").unwrap();
+
+        assert_eq!(header.get_all("").count(), 3);
+        assert_eq!(header.to_string(), "=foo; bar; =baz");
+
+        let mut header = CookieHeader::new();
+        header.push(Cookie::new("", "foo"));
+        assert_eq!(header.to_string(), "foo");
+    }
+
+    #[test]
+    fn test_header_empty() {
+        let header = CookieHeader::from_str("").unwrap();
+
+        assert!(header.is_empty());
+        assert_eq!(header.to_string(), "");
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// Checks if `host` is an IPv4 or IPv6 address. IPv6 addresses may be enclosed in brackets.
+pub fn is_ip_address(host: &str) -> bool {
+    if Ipv4Addr::from_str(host).is_ok() {
+        return true;
+    }
+    let host = host.strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host);
+    return Ipv6Addr::from_str(host).is_ok();
+}
+
+/// Checks if `host` domain-matches `domain`, using
+/// [RFC6265 Section 5.1.3](https://datatracker.ietf.org/doc/html/rfc6265#section-5.1.3) algorithm:
+///
+/// * The comparison is case-insensitive.
+/// * `host` and `domain` are identical, or
+/// * `domain` is a suffix of `host`, the character of `host` before the suffix is a dot and
+///   `host` is a host name, not an IP address.
+///
+/// A leading dot in `domain` is ignored.
+///
+/// ```rust
+/// use wcookie::domain_match;
+///
+/// assert!(domain_match("www.Example.com", "example.com"));
+/// assert!(!domain_match("example.com.evil.org", "example.com"));
+/// assert!(!domain_match("notexample.com", "example.com"));
+/// assert!(!domain_match("1.2.3.4", "3.4"));
+/// ```
+pub fn domain_match(host: &str, domain: &str) -> bool {
+    let domain = domain.strip_prefix('.').unwrap_or(domain);
+
+    if domain.is_empty() || host.is_empty() {
+        return false;
+    }
+
+    if host.eq_ignore_ascii_case(domain) {
+        return true;
+    }
+
+    if host.len() <= domain.len() || is_ip_address(host) {
+        return false;
+    }
+
+    let host = host.as_bytes();
+    let boundary = host.len() - domain.len();
+
+    return host[boundary..].eq_ignore_ascii_case(domain.as_bytes())
+        && host[boundary - 1] == b'.';
+}
@@ -0,0 +1,154 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// Kind of [ParseError]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// The cookie has not got `=` between name and value
+    Malformed,
+    /// The cookie name is empty
+    MissingName,
+    /// The cookie value is empty
+    EmptyValue,
+    /// A date, such as the `Expires` value, cannot be parsed
+    InvalidDate,
+    /// The `Max-Age` value is not a number of seconds
+    InvalidMaxAge,
+    /// The `SameSite` value is not `Strict`, `Lax` nor `None`
+    InvalidSameSite,
+    /// The `Priority` value is not `Low`, `Medium` nor `High`
+    InvalidPriority,
+    /// An attribute such as `Domain` or `Path` has not got a value
+    DirectiveNeedsValue,
+    /// A request URL cannot be parsed
+    InvalidUrl,
+    /// The cookie `Domain` does not domain-match the request host
+    DomainMismatch,
+    /// The cookie `Domain` is a public suffix
+    PublicSuffixDomain,
+    /// The cookie attributes do not meet the requirements of its name prefix, see [CookiePrefix](crate::CookiePrefix)
+    InvalidPrefix,
+    /// The cookie is `Partitioned` but it is not `Secure`
+    PartitionedNotSecure
+}
+
+/// Error type produced while parsing a `Cookie`.
+///
+/// Besides the message, it has got a [ParseErrorKind] and, when known, the byte offsets of the
+/// input text that caused the error:
+///
+/// ```rust
+/// use wcookie::{ParseErrorKind, SetCookie};
+/// use std::str::FromStr;
+///
+/// let input = "id=1; Path=/; Max-Age=abc";
+/// let error = SetCookie::from_str(input).err().unwrap();
+///
+/// assert_eq!(error.kind(), ParseErrorKind::InvalidMaxAge);
+/// assert_eq!(&input[error.span().unwrap()], "abc");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    span: Option<Range<usize>>,
+    details: Cow<'static, str>
+}
+
+impl ParseError {
+    /// Constructor with the error kind and any type of string. Static messages are not copied.
+    pub(crate) fn new<S>(kind: ParseErrorKind, msg: S) -> ParseError
+    where S: Into<Cow<'static, str>> {
+        ParseError{kind, span: None, details: msg.into()}
+    }
+
+    /// Sets the byte offsets of the input that caused the error
+    pub(crate) fn with_span(mut self, span: Range<usize>) -> ParseError {
+        self.span = Some(span);
+        self
+    }
+
+    /// Moves the error offsets, when the parsed text is a part of a larger input starting at `offset`
+    pub(crate) fn offset(mut self, offset: usize) -> ParseError {
+        self.span = self.span.map(|span| span.start + offset..span.end + offset);
+        self
+    }
+
+    /// Gets the error kind
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Gets the byte offsets of the input text that caused the error, if known
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.details)
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        &self.details
+    }
+}
+
+/// Kind of [ParseWarning]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ParseWarningKind {
+    /// The attribute appears several times, the last one is used
+    DuplicateAttribute,
+    /// The attribute is invalid and it was ignored, at [ParseMode::Lenient](crate::ParseMode::Lenient) mode
+    IgnoredAttribute,
+    /// The attribute is not known, it is kept at [SetCookie::extensions](crate::SetCookie::extensions)
+    UnknownAttribute,
+    /// The `Expires` date is in the past, so the cookie is expired
+    ExpiresInPast,
+    /// The cookie attributes do not meet the requirements of its name prefix, see [CookiePrefix](crate::CookiePrefix).
+    /// The cookie is rejected when it is stored.
+    InvalidPrefix
+}
+
+/// Non-fatal issue found while parsing a `Set-Cookie` value, see
+/// [SetCookie::parse_with_warnings](crate::SetCookie::parse_with_warnings)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Warning kind
+    pub kind: ParseWarningKind,
+    /// Attribute name in lowercase
+    pub attribute: String,
+    /// Byte offsets of the attribute at the input
+    pub span: Range<usize>
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self.kind {
+            ParseWarningKind::DuplicateAttribute => "Duplicate attribute",
+            ParseWarningKind::IgnoredAttribute => "Ignored invalid attribute",
+            ParseWarningKind::UnknownAttribute => "Unknown attribute",
+            ParseWarningKind::ExpiresInPast => "Expires date is in the past at attribute",
+            ParseWarningKind::InvalidPrefix => "Prefix requirements not met by cookie"
+        };
+        write!(f, "{} {} at {}..{}", message, self.attribute, self.span.start, self.span.end)
+    }
+}
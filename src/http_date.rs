@@ -0,0 +1,350 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::SystemTime;
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use crate::{MAX_EXPIRE_SECS, ParseError, ParseErrorKind};
+
+/// Month names as written at HTTP dates
+const MONTHS: [&[u8]; 12] = [b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun", b"Jul", b"Aug", b"Sep", b"Oct", b"Nov", b"Dec"];
+
+/// Short day of the week names
+const WEEKDAYS: [&[u8]; 7] = [b"Mon", b"Tue", b"Wed", b"Thu", b"Fri", b"Sat", b"Sun"];
+
+/// Long day of the week names, used by RFC 850 dates
+const LONG_WEEKDAYS: [&[u8]; 7] = [b"Monday", b"Tuesday", b"Wednesday", b"Thursday", b"Friday", b"Saturday", b"Sunday"];
+
+/// Date components found at a date: year, month, day, hour, minute and second
+type Fields = (i32, u32, u32, u32, u32, u32);
+
+/// Time components: hour, minute and second
+type Time = (u32, u32, u32);
+
+/// Date used by HTTP headers such as `Date`, `Last-Modified`, `Retry-After` or the `Expires` cookie attribute,
+/// as defined in [RFC7231 Section 7.1.1.1](https://datatracker.ietf.org/doc/html/rfc7231#section-7.1.1.1).
+///
+/// It is parsed from any of the three formats, IMF-fixdate, RFC 850 and asctime, and it is always written
+/// as IMF-fixdate. HTTP dates have not got fractions of second, so they are truncated.
+/// IMF-fixdate years have four digits, so dates are clamped between the UNIX epoch and the end of year 9999.
+///
+/// ```rust
+/// use wcookie::HttpDate;
+/// use std::str::FromStr;
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+///
+/// let date = HttpDate::from_str("Sunday, 06-Nov-94 08:49:37 GMT").unwrap();
+///
+/// assert_eq!(date.to_string(), "Sun, 06 Nov 1994 08:49:37 GMT");
+/// assert_eq!(date, HttpDate::from_str("Sun Nov  6 08:49:37 1994").unwrap());
+/// assert_eq!(SystemTime::from(date), UNIX_EPOCH + Duration::from_secs(784111777));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HttpDate(DateTime<Utc>);
+
+impl HttpDate {
+    /// Gets the current date
+    pub fn now() -> HttpDate {
+        HttpDate::from(Utc::now())
+    }
+}
+
+impl FromStr for HttpDate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<HttpDate, ParseError> {
+        return parse_http_date(s).map(HttpDate::from);
+    }
+}
+
+impl fmt::Display for HttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format("%a, %d %b %Y %H:%M:%S GMT"))
+    }
+}
+
+impl From<DateTime<Utc>> for HttpDate {
+    fn from(date: DateTime<Utc>) -> HttpDate {
+        let secs = date.timestamp();
+        if secs < 0 || secs > MAX_EXPIRE_SECS as i64 {
+            let clamped = DateTime::from_timestamp(secs.clamp(0, MAX_EXPIRE_SECS as i64), 0).unwrap_or_default();
+            return HttpDate(clamped);
+        }
+        return HttpDate(date.trunc_subsecs(0));
+    }
+}
+
+impl From<HttpDate> for DateTime<Utc> {
+    fn from(date: HttpDate) -> DateTime<Utc> {
+        date.0
+    }
+}
+
+impl From<SystemTime> for HttpDate {
+    fn from(time: SystemTime) -> HttpDate {
+        HttpDate::from(DateTime::<Utc>::from(time))
+    }
+}
+
+impl From<HttpDate> for SystemTime {
+    fn from(date: HttpDate) -> SystemTime {
+        SystemTime::from(date.0)
+    }
+}
+
+/// Parses a date in any of the three HTTP formats: RFC 1123 (IMF-fixdate), RFC 850 and asctime.
+///
+/// The result is the same as trying [parse_rfc_1123_date](crate::parse_rfc_1123_date),
+/// [parse_rfc_850_date](crate::parse_rfc_850_date) and [parse_asct_date](crate::parse_asct_date) in turn,
+/// but the date is parsed byte by byte, without regular expressions nor heap allocations.
+///
+/// ```rust
+/// use wcookie::{parse_http_date, parse_rfc_850_date};
+///
+/// let date = "Sunday, 06-Nov-94 08:49:37 GMT";
+/// assert_eq!(parse_http_date(date).unwrap(), parse_rfc_850_date(date).unwrap());
+/// ```
+pub fn parse_http_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
+    let bytes = date.as_bytes();
+    return rfc_1123(bytes)
+        .or_else(|| rfc_850(bytes))
+        .or_else(|| asctime(bytes))
+        .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"));
+}
+
+/// Parses a RFC 1123 date, such as `Sun, 06 Nov 1994 08:49:37 GMT`
+fn rfc_1123(date: &[u8]) -> Option<DateTime<Utc>> {
+    return search(date, |s| {
+        let s = weekday(s, &WEEKDAYS)?.strip_prefix(b", ")?;
+        let (day, s) = day(s)?;
+        let (month, s) = month(s.strip_prefix(b" ")?)?;
+        let (year, s) = number(s.strip_prefix(b" ")?, 4)?;
+        let ((hour, minute, second), s) = time(s.strip_prefix(b" ")?)?;
+        s.strip_prefix(b" GMT")?;
+        return Some((year as i32, month, day, hour, minute, second));
+    });
+}
+
+/// Parses a RFC 850 date, such as `Sunday, 06-Nov-94 08:49:37 GMT`
+fn rfc_850(date: &[u8]) -> Option<DateTime<Utc>> {
+    let rest = |s: &[u8]| -> Option<Fields> {
+        let (day, s) = day(s.strip_prefix(b", ")?)?;
+        let (month, s) = month(s.strip_prefix(b"-")?)?;
+        let s = s.strip_prefix(b"-")?;
+        let (mut year, s) = match number(s, 4) {
+            Some((year, s)) if s.starts_with(b" ") => (year, s),
+            _ => number(s, 2)?
+        };
+        let ((hour, minute, second), s) = time(s.strip_prefix(b" ")?)?;
+        s.strip_prefix(b" GMT")?;
+        // Fix millenium, for 2 digit year
+        year += if year < 70 { 2000 } else if year < 100 { 1900 } else { 0 };
+        return Some((year as i32, month, day, hour, minute, second));
+    };
+
+    // Long names first, as `Monday` also starts with `Mon`
+    return search(date, |s| {
+        weekday(s, &LONG_WEEKDAYS).and_then(rest).or_else(|| weekday(s, &WEEKDAYS).and_then(rest))
+    });
+}
+
+/// Parses an asctime date, such as `Sun Nov  6 08:49:37 1994`
+fn asctime(date: &[u8]) -> Option<DateTime<Utc>> {
+    return search(date, |s| {
+        let (month, s) = month(weekday(s, &WEEKDAYS)?.strip_prefix(b" ")?)?;
+        let s = s.strip_prefix(b" ")?;
+        let s = s.strip_prefix(b" ").unwrap_or(s);
+        let (day, s) = match s {
+            [d @ b'1'..=b'9', b' ', ..] => (u32::from(d - b'0'), &s[1..]),
+            _ => day(s)?
+        };
+        let ((hour, minute, second), s) = time(s.strip_prefix(b" ")?)?;
+        let (year, _) = number(s.strip_prefix(b" ")?, 4)?;
+        return Some((year as i32, month, day, hour, minute, second));
+    });
+}
+
+/// Finds the first position of `date` where `matcher` finds the date components, and builds the date.
+/// Like a regular expression search, the date may have any text before and after.
+fn search<F>(date: &[u8], matcher: F) -> Option<DateTime<Utc>>
+where F: Fn(&[u8]) -> Option<Fields> {
+    let (year, month, day, hour, minute, second) = (0..date.len()).find_map(|start| matcher(&date[start..]))?;
+    let naive = NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(hour, minute, second)?;
+    return Some(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
+}
+
+/// Parses exactly `count` digits at the start of `s`
+fn number(s: &[u8], count: usize) -> Option<(u32, &[u8])> {
+    let digits = s.get(0..count)?;
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let value = digits.iter().fold(0, |n, c| n * 10 + u32::from(c - b'0'));
+    return Some((value, &s[count..]));
+}
+
+/// Parses two digits lower or equal than `max`
+fn bounded(s: &[u8], max: u32) -> Option<(u32, &[u8])> {
+    return number(s, 2).filter(|(value, _)| *value <= max);
+}
+
+/// Parses a two digit day of the month: `01` to `39`, invalid days are rejected when the date is built
+fn day(s: &[u8]) -> Option<(u32, &[u8])> {
+    return bounded(s, 39).filter(|(day, _)| *day > 0);
+}
+
+/// Parses a month name
+fn month(s: &[u8]) -> Option<(u32, &[u8])> {
+    let name = s.get(0..3)?;
+    let index = MONTHS.iter().position(|month| *month == name)?;
+    return Some((index as u32 + 1, &s[3..]));
+}
+
+/// Skips the day of the week, one of `names`
+fn weekday<'a>(s: &'a [u8], names: &[&[u8]]) -> Option<&'a [u8]> {
+    return names.iter().find_map(|name| s.strip_prefix(*name));
+}
+
+/// Parses a `HH:MM:SS` time
+fn time(s: &[u8]) -> Option<(Time, &[u8])> {
+    let (hour, s) = bounded(s, 23)?;
+    let (minute, s) = bounded(s.strip_prefix(b":")?, 59)?;
+    let (second, s) = bounded(s.strip_prefix(b":")?, 59)?;
+    return Some(((hour, minute, second), s));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use chrono::{DateTime, TimeZone, Utc};
+    use crate::{HttpDate, parse_asct_date, parse_http_date, parse_rfc_1123_date, parse_rfc_850_date};
+    use super::{asctime, rfc_1123, rfc_850};
+
+    /// Characters used to build and mutate dates at the differential test
+    const ALPHABET: &[u8] = b" ,-:0123456789GMTJanFebNovDecMonSundayWedsx";
+
+    /// Small xorshift generator, so the test is repeatable
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, max: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % max as u64) as usize
+        }
+
+        fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+            items[self.next(items.len())]
+        }
+    }
+
+    fn random_date(random: &mut Random) -> String {
+        let weekday = random.pick(&["Mon", "Sun", "Wed", "Sunday", "Wednesday", "Mo"]);
+        let month = random.pick(&["Jan", "Feb", "Nov", "Dec", "jan", "Foo"]);
+        let day = random.next(42);
+        let year = random.pick(&["1994", "2037", "94", "69", "70", "0050", "199", "19945"]);
+        let (hour, minute, second) = (random.next(26), random.next(62), random.next(62));
+
+        return match random.next(4) {
+            0 => format!("{}, {:02} {} {} {:02}:{:02}:{:02} GMT", weekday, day, month, year, hour, minute, second),
+            1 => format!("{}, {:02}-{}-{} {:02}:{:02}:{:02} GMT", weekday, day, month, year, hour, minute, second),
+            2 => format!("{} {} {:2} {:02}:{:02}:{:02} {}", weekday, month, day, hour, minute, second, year),
+            _ => format!("{} {} {} {:02}:{:02}:{:02} {}", weekday, month, day, hour, minute, second, year)
+        };
+    }
+
+    fn mutate(random: &mut Random, date: &mut Vec<u8>) {
+        for _ in 0..random.next(3) {
+            let index = random.next(date.len() + 1);
+            let c = ALPHABET[random.next(ALPHABET.len())];
+            match random.next(3) {
+                0 if index < date.len() => date[index] = c,
+                1 if index < date.len() => { date.remove(index); },
+                _ => date.insert(index, c)
+            }
+        }
+    }
+
+    #[test]
+    fn test_http_date_differential() {
+        let mut random = Random(0x2545F4914F6CDD1D);
+        let mut matches = 0;
+
+        for _ in 0..50_000 {
+            let mut date = random_date(&mut random).into_bytes();
+            mutate(&mut random, &mut date);
+            let date = String::from_utf8(date).unwrap();
+
+            assert_eq!(rfc_1123(date.as_bytes()), parse_rfc_1123_date(&date).ok(), "{:?}", date);
+            assert_eq!(rfc_850(date.as_bytes()), parse_rfc_850_date(&date).ok(), "{:?}", date);
+            assert_eq!(asctime(date.as_bytes()), parse_asct_date(&date).ok(), "{:?}", date);
+
+            let expected = parse_rfc_1123_date(&date)
+                .or_else(|_| parse_rfc_850_date(&date))
+                .or_else(|_| parse_asct_date(&date));
+            assert_eq!(parse_http_date(&date).ok(), expected.as_ref().ok().copied(), "{:?}", date);
+            if expected.is_ok() {
+                matches += 1;
+            }
+        }
+
+        // Both valid and invalid dates are checked
+        assert!(matches > 2_500, "{}", matches);
+    }
+
+    #[test]
+    fn test_http_date_formats() {
+        let expected = HttpDate::from(Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap());
+
+        assert_eq!(HttpDate::from_str("Sun, 06 Nov 1994 08:49:37 GMT").unwrap(), expected);
+        assert_eq!(HttpDate::from_str("Sunday, 06-Nov-94 08:49:37 GMT").unwrap(), expected);
+        assert_eq!(HttpDate::from_str("Sun Nov  6 08:49:37 1994").unwrap(), expected);
+        assert!(HttpDate::from_str("06 Nov 1994 08:49:37").is_err());
+        assert!(HttpDate::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_http_date_display() {
+        let date = HttpDate::from(Utc.with_ymd_and_hms(2015, 10, 1, 7, 8, 9).unwrap());
+
+        assert_eq!(date.to_string(), "Thu, 01 Oct 2015 07:08:09 GMT");
+        assert_eq!(HttpDate::from_str(&date.to_string()).unwrap(), date);
+    }
+
+    #[test]
+    fn test_http_date_conversions() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_445_412_480_750);
+        let date = HttpDate::from(time);
+
+        assert_eq!(SystemTime::from(date), UNIX_EPOCH + Duration::from_secs(1_445_412_480));
+        assert_eq!(DateTime::<Utc>::from(date), Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap());
+        assert!(HttpDate::now() <= HttpDate::from(SystemTime::now()));
+    }
+
+    #[test]
+    fn test_http_date_out_of_range() {
+        let late = HttpDate::from(Utc.with_ymd_and_hms(11476, 8, 15, 5, 20, 0).unwrap());
+        assert_eq!(late.to_string(), "Fri, 31 Dec 9999 23:59:59 GMT");
+
+        let early = HttpDate::from(Utc.with_ymd_and_hms(999, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(early.to_string(), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(SystemTime::from(early), UNIX_EPOCH);
+
+        let last = Utc.with_ymd_and_hms(9999, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(DateTime::<Utc>::from(HttpDate::from(last)), last);
+    }
+}
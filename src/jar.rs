@@ -0,0 +1,1582 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use crate::url::http_scheme;
+use crate::{Clock, Cookie, CookiePolicy, CookiePriority, EvictionEvent, EvictionReason, JarLimits, ParseError, ParseMode, PublicSuffixList,
+    RequestContext, RequestUrl, SetCookie, SystemClock, domain_match};
+
+/// Default maximum cookie lifetime, 400 days, as defined in
+/// [RFC6265bis Section 5.5](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.5)
+pub const DEFAULT_MAX_LIFETIME: Duration = Duration::from_secs(400 * 24 * 60 * 60);
+
+/// Cookie kept at a [CookieJar], with the fields of the
+/// [RFC6265 Section 5.3](https://datatracker.ietf.org/doc/html/rfc6265#section-5.3) storage model.
+///
+/// The inner [SetCookie] always has its `domain`, `host_only` and `path` set to the values used by the jar.
+#[derive(Debug, Clone)]
+pub struct StoredCookie {
+    /// Stored cookie, with effective `domain`, `host_only` and `path`
+    pub cookie: SetCookie,
+    /// Time the cookie was first stored. It is kept when the cookie is replaced.
+    pub creation_time: SystemTime,
+    /// Last time the cookie was stored or included in a request
+    pub last_access_time: SystemTime,
+    /// The cookie has `Expires` or `Max-Age`, otherwise it is a session cookie
+    pub persistent: bool,
+    /// The cookie expired later than the jar maximum lifetime, so its lifetime was capped,
+    /// see [CookieJar::set_max_lifetime]
+    pub lifetime_capped: bool,
+    /// Key of the partition where a `Partitioned` cookie is stored, see [CookieJar::set_partitioned_storage].
+    /// `None` for cookies that are not partitioned.
+    pub partition_key: Option<String>,
+    /// Scheme of the request that set the cookie, used by [CookiePolicy::origin_bound]
+    pub source_scheme: String,
+    /// Port of the request that set the cookie, see [RequestUrl::port_or_default], used by
+    /// [CookiePolicy::origin_bound]
+    pub source_port: Option<u16>
+}
+
+impl StoredCookie {
+    /// Cookie name
+    pub fn name(&self) -> &str {
+        &self.cookie.name
+    }
+
+    /// Cookie domain
+    pub fn domain(&self) -> &str {
+        self.cookie.domain.as_deref().unwrap_or_default()
+    }
+
+    /// Cookie path
+    pub fn path(&self) -> &str {
+        self.cookie.path_or_default()
+    }
+
+    /// Size of the cookie in bytes, used by [JarLimits]: the sum of the lengths of name, value, domain, path
+    /// and extensions
+    pub fn size(&self) -> usize {
+        cookie_size(&self.cookie)
+    }
+
+    /// Checks if the stored cookie is expired
+    pub fn expired(&self) -> bool {
+        return self.cookie.expired();
+    }
+
+    /// Checks if the stored cookie is expired at the time `now`
+    pub fn expired_at(&self, now: SystemTime) -> bool {
+        return self.cookie.expired_at(now);
+    }
+
+    /// Checks if the cookie can be sent in a request with `context`, using
+    /// [RFC6265 Section 5.4](https://datatracker.ietf.org/doc/html/rfc6265#section-5.4) criteria:
+    /// domain (or host for host-only cookies), path and `Secure`; and `SameSite`
+    /// (see [SetCookie::same_site_allows]). Partitioned cookies are only sent in their partition.
+    pub fn matches(&self, context: &RequestContext) -> bool {
+        return self.matches_with_policy(context, &CookiePolicy::default());
+    }
+
+    /// Checks if the cookie can be sent in a request with `context`, as [StoredCookie::matches] does, with the
+    /// browser behaviors enabled at `policy`:
+    ///
+    /// * [CookiePolicy::schemeful_same_site], [CookiePolicy::default_same_site] and
+    ///   [CookiePolicy::lax_allowing_unsafe]: see [SetCookie::same_site_allows_with_policy]. The cookie age is
+    ///   counted from its `creation_time`.
+    /// * [CookiePolicy::origin_bound]: the request must have the scheme and port of the request that set the cookie.
+    ///   WebSocket schemes are compared as `http` and `https`.
+    pub fn matches_with_policy(&self, context: &RequestContext, policy: &CookiePolicy) -> bool {
+        return self.matches_at(context, policy, SystemTime::now());
+    }
+
+    /// Checks if the cookie can be sent in a request with `context` at the time `now`, for example, the time of the
+    /// jar [Clock], see [StoredCookie::matches_with_policy]. Expired cookies do not match.
+    ///
+    /// Sites are compared with the bundled [PublicSuffixList], see [StoredCookie::matches_with_list].
+    pub fn matches_at(&self, context: &RequestContext, policy: &CookiePolicy, now: SystemTime) -> bool {
+        return self.matches_with_list(context, policy, now, &PublicSuffixList::bundled());
+    }
+
+    /// Checks if the cookie can be sent in a request with `context` at the time `now`, as
+    /// [StoredCookie::matches_at] does, getting sites with `public_suffix_list`, as a [CookieJar] does with
+    /// its list, see [CookieJar::set_public_suffix_list]
+    pub fn matches_with_list(&self, context: &RequestContext, policy: &CookiePolicy, now: SystemTime,
+        public_suffix_list: &PublicSuffixList) -> bool {
+        let url = &context.url;
+
+        if self.expired_at(now) {
+            return false;
+        }
+
+        if let Some(ref partition_key) = self.partition_key {
+            if *partition_key != context.partition_key_with_list(public_suffix_list) {
+                return false;
+            }
+        }
+
+        if self.cookie.host_only {
+            if !self.domain().eq_ignore_ascii_case(&url.host) {
+                return false;
+            }
+        } else if !domain_match(&url.host, self.domain()) {
+            return false;
+        }
+
+        if self.cookie.secure && !url.is_secure() {
+            return false;
+        }
+
+        if policy.origin_bound && !self.same_origin(url) {
+            return false;
+        }
+
+        if !self.cookie.same_site_allows_since(context, policy, self.creation_time, now, public_suffix_list) {
+            return false;
+        }
+
+        return self.cookie.use_in_request_path(&url.path);
+    }
+
+    /// Checks if `url` has the scheme and port of the request that set the cookie
+    fn same_origin(&self, url: &RequestUrl) -> bool {
+        return http_scheme(&self.source_scheme) == url.http_scheme() && self.source_port == url.port_or_default();
+    }
+}
+
+/// Size of a cookie bound to a request, see [StoredCookie::size]
+fn cookie_size(cookie: &SetCookie) -> usize {
+    let extensions: usize = cookie.extensions.iter()
+        .map(|(key, value)| key.len() + value.as_ref().map_or(0, String::len))
+        .sum();
+    let domain = cookie.domain.as_deref().unwrap_or_default();
+    return cookie.name.len() + cookie.value.len() + domain.len() + cookie.path_or_default().len() + extensions;
+}
+
+/// Key of a cookie at the jar: partition, name, domain and path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CookieKey {
+    partition: Option<String>,
+    name: String,
+    domain: String,
+    path: String
+}
+
+impl CookieKey {
+    fn of(cookie: &SetCookie, partition: Option<String>) -> CookieKey {
+        CookieKey {
+            partition,
+            name: cookie.name.clone(),
+            domain: cookie.domain.clone().unwrap_or_default(),
+            path: String::from(cookie.path_or_default())
+        }
+    }
+
+    fn new(partition: Option<&str>, name: &str, domain: &str, path: &str) -> CookieKey {
+        CookieKey {
+            partition: partition.map(String::from),
+            name: String::from(name),
+            domain: domain.to_ascii_lowercase(),
+            path: String::from(path)
+        }
+    }
+}
+
+/// Cookie storage for user agents, following [RFC6265 Section 5.3](https://datatracker.ietf.org/doc/html/rfc6265#section-5.3).
+///
+/// Cookies received at `Set-Cookie` headers are stored with the URL of the request that
+/// produced the response:
+///
+/// ```rust
+/// use wcookie::CookieJar;
+///
+/// let mut jar = CookieJar::new();
+///
+/// assert!(jar.store("id=a3fWa; Max-Age=3600", "https://www.example.com/").unwrap());
+/// assert!(jar.store("lang=en; Domain=example.com", "https://www.example.com/").unwrap());
+///
+/// let id = jar.get("id", "www.example.com", "/").unwrap();
+/// assert!(id.cookie.host_only);
+/// assert!(id.persistent);
+///
+/// let lang = jar.get("lang", "example.com", "/").unwrap();
+/// assert!(!lang.cookie.host_only);
+/// assert!(!lang.persistent);
+///
+/// // An expired cookie deletes the stored one
+/// assert!(!jar.store("id=a3fWa; Expires=Thu, 01 Jan 1970 00:00:00 GMT", "https://www.example.com/").unwrap());
+/// assert!(jar.get("id", "www.example.com", "/").is_none());
+/// ```
+///
+/// Cookies with a `Domain` that is a public suffix, such as `com` or `co.uk`, are rejected unless
+/// the domain is the request host. The jar uses the bundled [PublicSuffixList], a newer one can be
+/// set with [CookieJar::set_public_suffix_list].
+#[derive(Debug, Clone)]
+pub struct CookieJar {
+    cookies: HashMap<CookieKey, StoredCookie>,
+    public_suffix_list: Arc<PublicSuffixList>,
+    max_lifetime: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    partitioned_storage: bool,
+    limits: JarLimits,
+    evictions: Vec<EvictionEvent>,
+    policy: CookiePolicy
+}
+
+impl Default for CookieJar {
+    fn default() -> Self {
+        CookieJar::new()
+    }
+}
+
+impl CookieJar {
+    /// Creates an empty jar
+    pub fn new() -> CookieJar {
+        CookieJar {
+            cookies: HashMap::new(),
+            public_suffix_list: PublicSuffixList::bundled(),
+            max_lifetime: Some(DEFAULT_MAX_LIFETIME),
+            clock: Arc::new(SystemClock),
+            partitioned_storage: false,
+            limits: JarLimits::default(),
+            evictions: Vec::new(),
+            policy: CookiePolicy::default()
+        }
+    }
+
+    /// Sets the Public Suffix List used to reject cookies for public suffixes
+    ///
+    /// ```no_run
+    /// use wcookie::{CookieJar, PublicSuffixList};
+    /// use std::sync::Arc;
+    ///
+    /// let list = PublicSuffixList::from_file("public_suffix_list.dat").unwrap();
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.set_public_suffix_list(Arc::new(list));
+    /// ```
+    pub fn set_public_suffix_list(&mut self, list: Arc<PublicSuffixList>) {
+        self.public_suffix_list = list;
+    }
+
+    /// Gets the Public Suffix List used by the jar
+    pub fn public_suffix_list(&self) -> &PublicSuffixList {
+        &self.public_suffix_list
+    }
+
+    /// Sets the clock used to get creation and access times and to check if cookies are expired,
+    /// [SystemClock] by default
+    ///
+    /// ```rust
+    /// use wcookie::{CookieJar, ManualClock};
+    /// use std::sync::Arc;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_000_000)));
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.set_clock(clock.clone());
+    ///
+    /// jar.store("id=1; Max-Age=60", "https://example.com/").unwrap();
+    /// assert!(jar.cookie_header_for("https://example.com/").unwrap().is_some());
+    ///
+    /// clock.advance(Duration::from_secs(60));
+    /// assert!(jar.cookie_header_for("https://example.com/").unwrap().is_none());
+    /// ```
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Gets the clock used by the jar
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Enables partitioned storage for `Partitioned` cookies (CHIPS), disabled by default.
+    ///
+    /// When it is enabled, `Partitioned` cookies are stored in a separate partition for each top-level site,
+    /// see [RequestContext::partition_key], and they are only sent in requests from that top-level site.
+    /// So an embedded widget gets a separate cookie for each site that embeds it. Other cookies are shared.
+    /// When it is disabled, `Partitioned` cookies are stored as any other cookie.
+    ///
+    /// ```rust
+    /// use wcookie::{CookieJar, RequestContext, RequestKind, RequestUrl};
+    /// use std::str::FromStr;
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.set_partitioned_storage(true);
+    ///
+    /// // Widget embedded at example.com
+    /// let mut context = RequestContext::new("https://widget.org/").unwrap();
+    /// context.site_for_cookies = Some(RequestUrl::from_str("https://example.com/").unwrap());
+    /// context.kind = RequestKind::Subresource;
+    /// jar.store_for_context("id=1; Secure; SameSite=None; Partitioned", &context).unwrap();
+    /// assert_eq!(jar.cookie_header_for_context(&context).as_deref(), Some("id=1"));
+    ///
+    /// // The same widget embedded at other.org
+    /// context.site_for_cookies = Some(RequestUrl::from_str("https://other.org/").unwrap());
+    /// assert!(jar.cookie_header_for_context(&context).is_none());
+    /// ```
+    pub fn set_partitioned_storage(&mut self, enabled: bool) {
+        self.partitioned_storage = enabled;
+    }
+
+    /// Checks if partitioned storage is enabled, see [CookieJar::set_partitioned_storage]
+    pub fn partitioned_storage(&self) -> bool {
+        self.partitioned_storage
+    }
+
+    /// Sets the browser behaviors used to select the cookies sent in requests, [CookiePolicy::default] by default.
+    ///
+    /// ```rust
+    /// use wcookie::{CookieJar, CookiePolicy};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.set_policy(CookiePolicy { origin_bound: true, ..CookiePolicy::default() });
+    ///
+    /// jar.store("id=1; Domain=example.com", "https://www.example.com/").unwrap();
+    ///
+    /// assert_eq!(jar.cookie_header_for("https://api.example.com/").unwrap().as_deref(), Some("id=1"));
+    /// assert!(jar.cookie_header_for("http://www.example.com/").unwrap().is_none());
+    /// assert!(jar.cookie_header_for("https://www.example.com:8443/").unwrap().is_none());
+    /// ```
+    pub fn set_policy(&mut self, policy: CookiePolicy) {
+        self.policy = policy;
+    }
+
+    /// Gets the browser behaviors used to select the cookies sent in requests, see [CookieJar::set_policy]
+    pub fn policy(&self) -> &CookiePolicy {
+        &self.policy
+    }
+
+    /// Sets the storage limits, [JarLimits::default] by default.
+    ///
+    /// * Cookies larger than the maximum cookie size are not stored.
+    /// * When a new cookie exceeds the per-domain limit, cookies of the domain are removed as defined in
+    ///   [RFC6265bis Section 5.7](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.7):
+    ///   expired cookies first, then cookies with lower [CookiePriority] and, for the same
+    ///   priority, the least recently accessed ones.
+    /// * When a new cookie exceeds the total count or size limits, expired cookies and then the least recently
+    ///   accessed cookies of the jar are removed.
+    ///
+    /// Removed cookies are reported, see [CookieJar::take_evictions].
+    ///
+    /// ```rust
+    /// use wcookie::{CookieJar, EvictionReason, JarLimits};
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.set_limits(JarLimits { max_cookies: Some(2), ..JarLimits::default() });
+    ///
+    /// jar.store("a=1", "https://example.com/").unwrap();
+    /// jar.store("b=1", "https://example.org/").unwrap();
+    /// jar.cookie_header_for("https://example.com/").unwrap();
+    /// jar.store("c=1", "https://example.net/").unwrap();
+    ///
+    /// let evictions = jar.take_evictions();
+    /// assert_eq!(evictions.len(), 1);
+    /// assert_eq!(evictions[0].cookie.name(), "b");
+    /// assert_eq!(evictions[0].reason, EvictionReason::TotalLimit);
+    /// ```
+    pub fn set_limits(&mut self, limits: JarLimits) {
+        self.limits = limits;
+    }
+
+    /// Gets the storage limits
+    pub fn limits(&self) -> &JarLimits {
+        &self.limits
+    }
+
+    /// Gets and clears the cookies removed to meet the storage limits since the last call, in removal order.
+    /// See [CookieJar::set_limits].
+    ///
+    /// Events are kept until this function is called.
+    pub fn take_evictions(&mut self) -> Vec<EvictionEvent> {
+        std::mem::take(&mut self.evictions)
+    }
+
+    /// Sets the maximum number of cookies for each domain, see [CookieJar::set_limits]. `None` disables the limit.
+    ///
+    /// Cookies of a domain and its subdomains, that is, with the same registrable domain, count together.
+    ///
+    /// ```rust
+    /// use wcookie::CookieJar;
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.set_max_cookies_per_domain(Some(2));
+    ///
+    /// jar.store("a=1; Priority=High", "https://example.com/").unwrap();
+    /// jar.store("b=1; Priority=Low", "https://example.com/").unwrap();
+    /// jar.store("c=1", "https://www.example.com/").unwrap();
+    ///
+    /// assert_eq!(jar.len(), 2);
+    /// assert!(jar.get("b", "example.com", "/").is_none());
+    /// ```
+    pub fn set_max_cookies_per_domain(&mut self, max: Option<usize>) {
+        self.limits.max_cookies_per_domain = max;
+    }
+
+    /// Gets the maximum number of cookies for each domain, if any
+    pub fn max_cookies_per_domain(&self) -> Option<usize> {
+        self.limits.max_cookies_per_domain
+    }
+
+    /// Sets the maximum lifetime of stored cookies, [DEFAULT_MAX_LIFETIME] by default. Cookies that
+    /// expire later are stored with this lifetime, see [SetCookie::cap_lifetime]. `None` disables the limit.
+    ///
+    /// ```rust
+    /// use wcookie::CookieJar;
+    /// use std::time::Duration;
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.set_max_lifetime(Some(Duration::from_secs(3600)));
+    ///
+    /// jar.store("id=1; Max-Age=86400", "https://example.com/").unwrap();
+    ///
+    /// let stored = jar.get("id", "example.com", "/").unwrap();
+    /// assert!(stored.lifetime_capped);
+    /// assert_eq!(stored.cookie.max_age, Some(Duration::from_secs(3600)));
+    /// ```
+    pub fn set_max_lifetime(&mut self, max_lifetime: Option<Duration>) {
+        self.max_lifetime = max_lifetime;
+    }
+
+    /// Gets the maximum lifetime of stored cookies, if any
+    pub fn max_lifetime(&self) -> Option<Duration> {
+        self.max_lifetime
+    }
+
+    /// Parses a `Set-Cookie` header value received from `request_url` and stores the cookie.
+    ///
+    /// The value is parsed as user agents do, at [ParseMode::Lenient] mode: invalid attributes are ignored.
+    ///
+    /// Returns `true` if the cookie was stored, or `false` if it was ignored, it was evicted to meet the
+    /// jar limits or it removed a stored cookie because it is expired. See [CookieJar::store_cookie].
+    pub fn store(&mut self, set_cookie: &str, request_url: &str) -> Result<bool, ParseError> {
+        let cookie = SetCookie::parse_at(set_cookie, ParseMode::Lenient, self.clock.now())?;
+        return self.store_cookie(cookie, request_url);
+    }
+
+    /// Parses a `Set-Cookie` header value received in the response to a request with `context` and stores the cookie,
+    /// see [CookieJar::store].
+    pub fn store_for_context(&mut self, set_cookie: &str, context: &RequestContext) -> Result<bool, ParseError> {
+        let cookie = SetCookie::parse_at(set_cookie, ParseMode::Lenient, self.clock.now())?;
+        return Ok(self.store_cookie_for_context(cookie, context));
+    }
+
+    /// Stores a cookie received from `request_url`.
+    ///
+    /// * If the cookie has not got a `Domain`, it is host-only and its domain is the request host.
+    /// * If the cookie `Domain` does not match the request host, the cookie is ignored.
+    /// * If the cookie `Domain` is a public suffix, it is ignored unless the domain is the request host. In that case
+    ///   the cookie is host-only.
+    /// * If the cookie has not got a `Path`, its path is the [default_path](crate::default_path) of the request.
+    /// * If there is a cookie with the same name, domain and path, it is replaced, but its creation time is kept.
+    /// * If the cookie is expired, it is not stored and the cookie with the same name, domain and path is removed.
+    /// * If the cookie expires later than the maximum lifetime, see [CookieJar::set_max_lifetime], its lifetime is capped.
+    /// * If the cookie is too large, see [CookieJar::set_limits], it is not stored.
+    /// * If the request does not use a secure protocol, see [RequestUrl::is_secure](crate::RequestUrl::is_secure),
+    ///   `Secure` cookies are ignored, as well as cookies that would overwrite or shadow a stored `Secure` cookie
+    ///   with the same name, an overlapping domain and a path matched by the cookie path.
+    /// * If the domain or the jar has too many cookies, see [CookieJar::set_limits], some cookies are removed.
+    /// * The scheme and port of the request are recorded, see [CookiePolicy::origin_bound].
+    ///
+    /// The request is considered a same-site top-level navigation, see [CookieJar::store_cookie_for_context].
+    ///
+    /// The cookie is handled as received from the server, so its `host_only` flag is not kept and its creation time,
+    /// used to count `Max-Age`, is set to the jar clock, see [CookieJar::set_clock]. Use [CookieJar::restore] to
+    /// insert a saved cookie.
+    pub fn store_cookie(&mut self, cookie: SetCookie, request_url: &str) -> Result<bool, ParseError> {
+        let context = RequestContext::new(request_url)?;
+        return Ok(self.store_cookie_for_context(cookie, &context));
+    }
+
+    /// Stores a cookie received in the response to a request with `context`, see [CookieJar::store_cookie].
+    ///
+    /// If partitioned storage is enabled, see [CookieJar::set_partitioned_storage], `Partitioned` cookies are stored
+    /// at the partition of the request.
+    pub fn store_cookie_for_context(&mut self, mut cookie: SetCookie, context: &RequestContext) -> bool {
+        let now = self.clock.now();
+        cookie.set_created(now);
+
+        if cookie.bind_to_request(&context.url, &self.public_suffix_list).is_err() {
+            return false;
+        }
+
+        let partition_key = if self.partitioned_storage && cookie.partitioned {
+            Some(context.partition_key_with_list(&self.public_suffix_list))
+        } else {
+            None
+        };
+
+        if !context.url.is_secure() && (cookie.secure || self.shadows_secure_cookie(&cookie, partition_key.as_deref())) {
+            return false;
+        }
+
+        if self.limits.max_cookie_size.is_some_and(|max| cookie_size(&cookie) > max) {
+            return false;
+        }
+
+        let key = CookieKey::of(&cookie, partition_key.clone());
+        let old = self.cookies.remove(&key);
+
+        if cookie.expired_at(now) {
+            return false;
+        }
+
+        let creation_time = match old {
+            Some(old) => old.creation_time,
+            None => now
+        };
+
+        let lifetime_capped = match self.max_lifetime {
+            Some(max_lifetime) => cookie.cap_lifetime(max_lifetime),
+            None => false
+        };
+
+        let stored = StoredCookie {
+            persistent: cookie.expire_time().is_some(),
+            cookie,
+            creation_time,
+            last_access_time: now,
+            lifetime_capped,
+            partition_key,
+            source_scheme: context.url.scheme.clone(),
+            source_port: context.url.port_or_default()
+        };
+
+        let site = String::from(self.site_of(stored.domain()));
+        self.cookies.insert(key.clone(), stored);
+        self.enforce_limits(&site, now);
+
+        // The cookie may be evicted to meet the limits, for example, if it has a lower priority
+        return self.cookies.contains_key(&key);
+    }
+
+    /// Checks if a cookie received from a non-secure origin would overwrite or shadow a stored `Secure` cookie, as
+    /// defined in [RFC6265bis Section 5.7](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.7):
+    /// a `Secure` cookie of the same partition with the same name, whose domain domain-matches the cookie domain or
+    /// vice versa, and whose path is matched by the cookie path.
+    ///
+    /// These cookies are ignored so an `http` subdomain cannot replace the `Secure` cookies of its parent domain
+    /// ("cookie tossing").
+    fn shadows_secure_cookie(&self, cookie: &SetCookie, partition_key: Option<&str>) -> bool {
+        let domain = cookie.domain.as_deref().unwrap_or_default();
+        let path = cookie.path_or_default();
+
+        return self.cookies.values().any(|stored| {
+            stored.cookie.secure
+                && stored.partition_key.as_deref() == partition_key
+                && stored.name() == cookie.name
+                && (domain_match(stored.domain(), domain) || domain_match(domain, stored.domain()))
+                && stored.cookie.use_in_request_path(path)
+        });
+    }
+
+    /// Gets the site of a cookie domain, used to count cookies for each domain: its registrable domain,
+    /// or the domain itself for IP addresses and public suffixes
+    fn site_of<'a>(&self, domain: &'a str) -> &'a str {
+        self.public_suffix_list.registrable_domain(domain).unwrap_or(domain)
+    }
+
+    /// Removes cookies while `site` has more cookies than the per-domain limit, and while the jar has
+    /// more cookies or bytes than the total limits
+    fn enforce_limits(&mut self, site: &str, now: SystemTime) {
+        if let Some(max) = self.limits.max_cookies_per_domain {
+            let order = self.eviction_order(now, true, |stored| self.site_of(stored.domain()) == site);
+            if order.len() > max {
+                self.evict(order, now, EvictionReason::DomainLimit, max, usize::MAX);
+            }
+        }
+
+        let max_cookies = self.limits.max_cookies.unwrap_or(usize::MAX);
+        let max_total_size = self.limits.max_total_size.unwrap_or(usize::MAX);
+        if self.cookies.len() > max_cookies || self.total_size() > max_total_size {
+            let order = self.eviction_order(now, false, |_| true);
+            self.evict(order, now, EvictionReason::TotalLimit, max_cookies, max_total_size);
+        }
+    }
+
+    /// Sum of the sizes of the stored cookies
+    fn total_size(&self) -> usize {
+        self.cookies.values().map(StoredCookie::size).sum()
+    }
+
+    /// Gets the keys of the cookies that match `filter` in eviction order: expired cookies first, then cookies
+    /// with lower priority, if `by_priority`, and then the least recently accessed ones
+    fn eviction_order<F>(&self, now: SystemTime, by_priority: bool, filter: F) -> Vec<CookieKey>
+    where F: Fn(&StoredCookie) -> bool {
+        let mut candidates: Vec<(&CookieKey, &StoredCookie)> = self.cookies.iter()
+            .filter(|(_, stored)| filter(stored))
+            .collect();
+
+        candidates.sort_by_key(|(_, stored)| {
+            let priority = if by_priority { stored.cookie.priority } else { CookiePriority::Medium };
+            (!stored.expired_at(now), priority, stored.last_access_time)
+        });
+
+        return candidates.into_iter().map(|(key, _)| key.clone()).collect();
+    }
+
+    /// Removes the cookies of `order`, all of them of a group, until the group has at most `max_count` cookies and
+    /// `max_size` bytes. Expired cookies of the group are always removed.
+    fn evict(&mut self, order: Vec<CookieKey>, now: SystemTime, reason: EvictionReason, max_count: usize, max_size: usize) {
+        let mut count = order.len();
+        let mut size: usize = order.iter().filter_map(|key| self.cookies.get(key)).map(StoredCookie::size).sum();
+
+        for key in order {
+            let expired = match self.cookies.get(&key) {
+                Some(stored) => stored.expired_at(now),
+                None => continue
+            };
+
+            if !expired && count <= max_count && size <= max_size {
+                break;
+            }
+
+            if let Some(stored) = self.cookies.remove(&key) {
+                count -= 1;
+                size -= stored.size();
+                let reason = if expired {
+                    EvictionReason::Expired
+                } else if reason == EvictionReason::TotalLimit && count < max_count {
+                    EvictionReason::TotalSizeLimit
+                } else {
+                    reason
+                };
+                self.evictions.push(EvictionEvent { cookie: stored, reason });
+            }
+        }
+    }
+
+    /// Gets the cookies to be sent in a request to `request_url`, in the
+    /// [RFC6265 Section 5.4](https://datatracker.ietf.org/doc/html/rfc6265#section-5.4) order:
+    /// cookies with longer paths first and, for paths with the same length, earlier created cookies first.
+    ///
+    /// Expired cookies are removed from the jar and the last access time of returned cookies is updated.
+    /// 
+    /// The request is considered a same-site top-level navigation, see [CookieJar::cookies_for_context].
+    pub fn cookies_for(&mut self, request_url: &str) -> Result<Vec<Cookie>, ParseError> {
+        let context = RequestContext::new(request_url)?;
+        return Ok(self.cookies_for_context(&context));
+    }
+
+    /// Gets the cookies to be sent in a request with `context`, see [CookieJar::cookies_for].
+    pub fn cookies_for_context(&mut self, context: &RequestContext) -> Vec<Cookie> {
+        let now = self.clock.now();
+
+        self.remove_expired();
+
+        let mut selected: Vec<&mut StoredCookie> = self.cookies.values_mut()
+            .filter(|stored| stored.matches_with_list(context, &self.policy, now, &self.public_suffix_list))
+            .collect();
+
+        selected.sort_by(|a, b| {
+            b.path().len().cmp(&a.path().len())
+                .then(a.creation_time.cmp(&b.creation_time))
+        });
+
+        return selected.into_iter()
+            .map(|stored| {
+                stored.last_access_time = now;
+                stored.cookie.to_cookie()
+            })
+            .collect();
+    }
+
+    /// Gets the `Cookie` header value for a request to `request_url`, or `None` if no cookie
+    /// should be sent. Cookies are sorted as in [CookieJar::cookies_for] and joined with `"; "`.
+    ///
+    /// ```rust
+    /// use wcookie::CookieJar;
+    ///
+    /// let mut jar = CookieJar::new();
+    ///
+    /// jar.store("lang=en; Domain=example.com", "https://www.example.com/").unwrap();
+    /// jar.store("id=a3fWa; Path=/app; Secure", "https://www.example.com/").unwrap();
+    ///
+    /// let header = jar.cookie_header_for("https://www.example.com/app/index.html").unwrap();
+    /// assert_eq!(header.as_deref(), Some("id=a3fWa; lang=en"));
+    ///
+    /// let header = jar.cookie_header_for("http://example.com/app").unwrap();
+    /// assert_eq!(header.as_deref(), Some("lang=en"));
+    ///
+    /// assert!(jar.cookie_header_for("https://example.org/").unwrap().is_none());
+    /// ```
+    pub fn cookie_header_for(&mut self, request_url: &str) -> Result<Option<String>, ParseError> {
+        let context = RequestContext::new(request_url)?;
+        return Ok(self.cookie_header_for_context(&context));
+    }
+
+    /// Gets the `Cookie` header value for a request with `context`, see [CookieJar::cookie_header_for].
+    ///
+    /// ```rust
+    /// use wcookie::{CookieJar, RequestContext, RequestKind, RequestUrl};
+    /// use std::str::FromStr;
+    ///
+    /// let mut jar = CookieJar::new();
+    ///
+    /// jar.store("session=1; SameSite=Strict", "https://example.com/").unwrap();
+    /// jar.store("lang=en; SameSite=Lax", "https://example.com/").unwrap();
+    ///
+    /// // Link from other site
+    /// let mut context = RequestContext::new("https://example.com/").unwrap();
+    /// context.initiator = Some(RequestUrl::from_str("https://other.org/").unwrap());
+    /// assert_eq!(jar.cookie_header_for_context(&context).as_deref(), Some("lang=en"));
+    ///
+    /// // Image embedded at other site
+    /// context.site_for_cookies = context.initiator.clone();
+    /// context.kind = RequestKind::Subresource;
+    /// assert!(jar.cookie_header_for_context(&context).is_none());
+    /// ```
+    pub fn cookie_header_for_context(&mut self, context: &RequestContext) -> Option<String> {
+        let cookies = self.cookies_for_context(context);
+
+        if cookies.is_empty() {
+            return None;
+        }
+
+        let values: Vec<String> = cookies.iter().map(|cookie| cookie.to_string()).collect();
+        return Some(values.join("; "));
+    }
+
+    /// Gets the cookie stored with `name`, `domain` and `path`, out of any partition
+    pub fn get(&self, name: &str, domain: &str, path: &str) -> Option<&StoredCookie> {
+        return self.cookies.get(&CookieKey::new(None, name, domain, path));
+    }
+
+    /// Gets the cookie stored with `name`, `domain` and `path` at the partition with `partition_key`,
+    /// see [CookieJar::set_partitioned_storage]
+    pub fn get_partitioned(&self, partition_key: &str, name: &str, domain: &str, path: &str) -> Option<&StoredCookie> {
+        return self.cookies.get(&CookieKey::new(Some(partition_key), name, domain, path));
+    }
+
+    /// Removes the cookie stored with `name`, `domain` and `path`, out of any partition
+    pub fn remove(&mut self, name: &str, domain: &str, path: &str) -> Option<StoredCookie> {
+        return self.cookies.remove(&CookieKey::new(None, name, domain, path));
+    }
+
+    /// Removes the cookie stored with `name`, `domain` and `path` at the partition with `partition_key`,
+    /// see [CookieJar::set_partitioned_storage]
+    pub fn remove_partitioned(&mut self, partition_key: &str, name: &str, domain: &str, path: &str) -> Option<StoredCookie> {
+        return self.cookies.remove(&CookieKey::new(Some(partition_key), name, domain, path));
+    }
+
+    /// Inserts a cookie as it was saved, for example, from [CookieJar::iter], replacing the cookie with the same
+    /// partition, name, domain and path. Returns the replaced cookie, if any.
+    ///
+    /// Unlike [CookieJar::store_cookie], the cookie is not bound to a request nor checked: its `host_only` flag,
+    /// creation and last access times, partition and source are kept, so a jar can be rebuilt with its exact state.
+    /// The cookie `domain` must be in lowercase. Limits are not enforced.
+    ///
+    /// ```rust
+    /// use wcookie::CookieJar;
+    ///
+    /// let mut jar = CookieJar::new();
+    /// jar.store("id=1", "https://www.example.com/").unwrap();
+    ///
+    /// let saved: Vec<_> = jar.iter().cloned().collect();
+    ///
+    /// let mut restored = CookieJar::new();
+    /// for stored in saved {
+    ///     restored.restore(stored);
+    /// }
+    ///
+    /// let id = restored.get("id", "www.example.com", "/").unwrap();
+    /// assert!(id.cookie.host_only);
+    /// assert_eq!(id.creation_time, jar.get("id", "www.example.com", "/").unwrap().creation_time);
+    /// ```
+    pub fn restore(&mut self, stored: StoredCookie) -> Option<StoredCookie> {
+        let key = CookieKey::of(&stored.cookie, stored.partition_key.clone());
+        return self.cookies.insert(key, stored);
+    }
+
+    /// Removes all expired cookies
+    pub fn remove_expired(&mut self) {
+        let now = self.clock.now();
+        self.cookies.retain(|_, stored| !stored.expired_at(now));
+    }
+
+    /// Removes all session (not persistent) cookies, as user agents do when the session ends
+    pub fn remove_session_cookies(&mut self) {
+        self.cookies.retain(|_, stored| stored.persistent);
+    }
+
+    /// Removes all cookies
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    /// Iterates over the stored cookies, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &StoredCookie> {
+        self.cookies.values()
+    }
+
+    /// Number of stored cookies
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Checks if the jar has not got cookies
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+    use std::str::FromStr;
+    use crate::{Clock, CookieJar, CookiePolicy, EvictionReason, JarLimits, ManualClock, PublicSuffixList, RequestContext, RequestKind, RequestUrl, SetCookie,
+        DEFAULT_MAX_LIFETIME, LAX_ALLOWING_UNSAFE_MAX_AGE};
+
+    fn jar_with_clock() -> (CookieJar, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH + Duration::from_secs(1_600_000_000)));
+        let mut jar = CookieJar::new();
+        jar.set_clock(clock.clone());
+        (jar, clock)
+    }
+
+    #[test]
+    fn test_store_cookie_created_at_jar_clock() {
+        let (mut jar, clock) = jar_with_clock();
+
+        let mut cookie = SetCookie::new("id", "1");
+        cookie.max_age = Some(Duration::from_secs(60));
+        assert!(jar.store_cookie(cookie, "https://example.com/").unwrap());
+        assert_eq!(jar.get("id", "example.com", "/").unwrap().cookie.created(), clock.now());
+        assert_eq!(jar.cookie_header_for("https://example.com/").unwrap().as_deref(), Some("id=1"));
+
+        clock.advance(Duration::from_secs(3600));
+        assert!(jar.cookie_header_for("https://example.com/").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_store1() {
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store("id=1", "http://www.example.com/login").unwrap());
+
+        let stored = jar.get("id", "www.example.com", "/").unwrap();
+
+        assert!(stored.cookie.host_only);
+        assert!(!stored.persistent);
+        assert_eq!(stored.cookie.value, "1");
+        assert_eq!(stored.domain(), "www.example.com");
+        assert_eq!(jar.len(), 1);
+    }
+
+    #[test]
+    fn test_store2() {
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store("id=1; Domain=.Example.com; Path=/app; Max-Age=100", "http://www.example.com/").unwrap());
+
+        let stored = jar.get("id", "example.com", "/app").unwrap();
+
+        assert!(!stored.cookie.host_only);
+        assert!(stored.persistent);
+    }
+
+    #[test]
+    fn test_store_domain_mismatch() {
+        let mut jar = CookieJar::new();
+
+        assert!(!jar.store("id=1; Domain=other.com", "http://www.example.com/").unwrap());
+        assert!(!jar.store("id=1; Domain=..example.com", "http://www.example.com/").unwrap());
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn test_store_public_suffix() {
+        let mut jar = CookieJar::new();
+
+        assert!(!jar.store("id=1; Domain=com", "http://www.example.com/").unwrap());
+        assert!(!jar.store("id=1; Domain=co.uk", "http://www.example.co.uk/").unwrap());
+        assert!(!jar.store("id=1; Domain=.github.io", "https://user.github.io/").unwrap());
+        assert!(jar.store("id=1; Domain=example.co.uk", "http://www.example.co.uk/").unwrap());
+        assert_eq!(jar.len(), 1);
+    }
+
+    #[test]
+    fn test_store_public_suffix_host() {
+        let mut jar = CookieJar::new();
+
+        // The public suffix is the request host, the cookie is host-only
+        assert!(jar.store("id=1; Domain=github.io", "https://github.io/").unwrap());
+
+        let stored = jar.get("id", "github.io", "/").unwrap();
+        assert!(stored.cookie.host_only);
+        assert!(jar.cookie_header_for("https://user.github.io/").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_store_custom_public_suffix_list() {
+        let mut jar = CookieJar::new();
+        jar.set_public_suffix_list(Arc::new(PublicSuffixList::parse("com\nexample.com")));
+
+        assert!(!jar.store("id=1; Domain=example.com", "http://www.example.com/").unwrap());
+        assert!(jar.store("id=1; Domain=www.example.com", "http://a.www.example.com/").unwrap());
+    }
+
+    #[test]
+    fn test_custom_public_suffix_list_same_site() {
+        let mut jar = CookieJar::new();
+        jar.store("id=1; SameSite=Strict", "https://a.example.com/").unwrap();
+
+        // Link from b.example.com
+        let mut context = RequestContext::new("https://a.example.com/").unwrap();
+        context.initiator = Some(RequestUrl::from_str("https://b.example.com/").unwrap());
+        context.site_for_cookies = context.initiator.clone();
+        assert_eq!(jar.cookie_header_for_context(&context).as_deref(), Some("id=1"));
+
+        // a.example.com and b.example.com are different sites with the jar list
+        let list = Arc::new(PublicSuffixList::parse("com\nexample.com"));
+        assert!(!context.is_same_site_with_list(&list));
+        assert_eq!(context.partition_key_with_list(&list), "https://b.example.com");
+        jar.set_public_suffix_list(list);
+        assert!(jar.cookie_header_for_context(&context).is_none());
+    }
+
+    #[test]
+    fn test_store_replace() {
+        let (mut jar, clock) = jar_with_clock();
+
+        jar.store("id=1; Max-Age=100", "http://example.com/").unwrap();
+        let created = jar.get("id", "example.com", "/").unwrap().creation_time;
+
+        clock.advance(Duration::from_secs(5));
+        jar.store("id=2", "http://example.com/").unwrap();
+
+        let stored = jar.get("id", "example.com", "/").unwrap();
+        assert_eq!(stored.cookie.value, "2");
+        assert_eq!(stored.creation_time, created);
+        assert_eq!(stored.last_access_time, created + Duration::from_secs(5));
+        assert!(!stored.persistent);
+        assert_eq!(jar.len(), 1);
+    }
+
+    #[test]
+    fn test_store_same_name_different_path() {
+        let mut jar = CookieJar::new();
+
+        jar.store("id=1; Path=/a", "http://example.com/").unwrap();
+        jar.store("id=2; Path=/b", "http://example.com/").unwrap();
+
+        assert_eq!(jar.len(), 2);
+    }
+
+    #[test]
+    fn test_store_expired_deletes() {
+        let mut jar = CookieJar::new();
+
+        jar.store("id=1", "http://example.com/").unwrap();
+        assert!(!jar.store("id=1; Expires=Sun, 06 Nov 1994 08:49:37 GMT", "http://example.com/").unwrap());
+
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn test_store_lifetime_capped() {
+        let mut jar = CookieJar::new();
+
+        jar.store("a=1; Max-Age=315360000", "http://example.com/").unwrap();
+        jar.store("b=1; Expires=Fri, 31 Dec 9999 23:59:59 GMT", "http://example.com/").unwrap();
+        jar.store("c=1; Max-Age=34560000", "http://example.com/").unwrap();
+        jar.store("d=1", "http://example.com/").unwrap();
+
+        for name in ["a", "b"] {
+            let stored = jar.get(name, "example.com", "/").unwrap();
+            assert!(stored.lifetime_capped);
+            assert!(stored.persistent);
+            assert_eq!(stored.cookie.max_age, Some(DEFAULT_MAX_LIFETIME));
+        }
+
+        let stored = jar.get("c", "example.com", "/").unwrap();
+        assert!(!stored.lifetime_capped);
+        assert_eq!(stored.cookie.max_age, Some(Duration::from_secs(34560000)));
+
+        assert!(!jar.get("d", "example.com", "/").unwrap().lifetime_capped);
+    }
+
+    #[test]
+    fn test_clock_expiry() {
+        let (mut jar, clock) = jar_with_clock();
+
+        jar.store("a=1; Max-Age=60", "http://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("b=1; Expires=Sun, 13 Sep 2020 12:28:00 GMT", "http://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("c=1", "http://example.com/").unwrap();
+
+        clock.advance(Duration::from_secs(57));
+        assert_eq!(jar.cookie_header_for("http://example.com/").unwrap().as_deref(), Some("a=1; b=1; c=1"));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(jar.cookie_header_for("http://example.com/").unwrap().as_deref(), Some("b=1; c=1"));
+
+        clock.advance(Duration::from_secs(3600));
+        jar.remove_expired();
+        assert_eq!(jar.len(), 1);
+        assert!(jar.get("c", "example.com", "/").is_some());
+    }
+
+    #[test]
+    fn test_store_lifetime_unlimited() {
+        let mut jar = CookieJar::new();
+        jar.set_max_lifetime(None);
+
+        jar.store("a=1; Max-Age=315360000", "http://example.com/").unwrap();
+
+        let stored = jar.get("a", "example.com", "/").unwrap();
+        assert!(!stored.lifetime_capped);
+        assert_eq!(stored.cookie.max_age, Some(Duration::from_secs(315360000)));
+    }
+
+    #[test]
+    fn test_store_prefix() {
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store("__Host-a=1; Secure; Path=/", "https://example.com/app/").unwrap());
+        assert!(jar.store("__Secure-b=1; Secure; Domain=example.com", "https://www.example.com/").unwrap());
+        assert!(!jar.store("__Host-c=1; Secure", "https://example.com/app/").unwrap());
+        assert!(!jar.store("__Secure-d=1", "https://example.com/").unwrap());
+
+        assert_eq!(jar.len(), 2);
+    }
+
+    fn embedded(url: &str, top_level: &str) -> RequestContext {
+        let mut context = RequestContext::new(url).unwrap();
+        context.site_for_cookies = Some(RequestUrl::from_str(top_level).unwrap());
+        context.kind = RequestKind::Subresource;
+        context
+    }
+
+    #[test]
+    fn test_store_partitioned() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_partitioned_storage(true);
+
+        let at_example = embedded("https://widget.org/", "https://www.example.com/");
+        let at_other = embedded("https://widget.org/", "https://other.org/");
+
+        assert!(jar.store_for_context("shared=1; Secure; SameSite=None", &at_other).unwrap());
+        clock.advance(Duration::from_secs(1));
+        assert!(jar.store_for_context("id=1; Secure; SameSite=None; Partitioned", &at_example).unwrap());
+        assert!(jar.store_for_context("id=2; Secure; SameSite=None; Partitioned", &at_other).unwrap());
+        assert!(!jar.store_for_context("insecure=1; SameSite=None; Partitioned", &at_other).unwrap());
+
+        assert_eq!(jar.len(), 3);
+        assert_eq!(jar.get_partitioned("https://example.com", "id", "widget.org", "/").unwrap().cookie.value, "1");
+        assert_eq!(jar.get_partitioned("https://other.org", "id", "widget.org", "/").unwrap().cookie.value, "2");
+        assert!(jar.get("id", "widget.org", "/").is_none());
+        assert!(jar.get("shared", "widget.org", "/").unwrap().partition_key.is_none());
+
+        assert_eq!(jar.cookie_header_for_context(&at_example).as_deref(), Some("shared=1; id=1"));
+
+        // The http version of the top-level site has its own partition
+        let at_http_example = embedded("https://widget.org/", "http://www.example.com/");
+        assert_eq!(jar.cookie_header_for_context(&at_http_example).as_deref(), Some("shared=1"));
+
+        assert!(jar.remove_partitioned("https://example.com", "id", "widget.org", "/").is_some());
+        assert!(jar.remove("id", "widget.org", "/").is_none());
+        assert_eq!(jar.cookie_header_for_context(&at_example).as_deref(), Some("shared=1"));
+        assert_eq!(jar.len(), 2);
+        assert_eq!(jar.cookie_header_for_context(&at_other).as_deref(), Some("shared=1; id=2"));
+        assert_eq!(jar.cookie_header_for("https://widget.org/").unwrap().as_deref(), Some("shared=1"));
+    }
+
+    #[test]
+    fn test_store_partitioned_disabled() {
+        let mut jar = CookieJar::new();
+
+        let at_example = embedded("https://widget.org/", "https://www.example.com/");
+        let at_other = embedded("https://widget.org/", "https://other.org/");
+
+        jar.store_for_context("id=1; Secure; SameSite=None; Partitioned", &at_example).unwrap();
+        jar.store_for_context("id=2; Secure; SameSite=None; Partitioned", &at_other).unwrap();
+
+        assert_eq!(jar.len(), 1);
+        let stored = jar.get("id", "widget.org", "/").unwrap();
+        assert!(stored.cookie.partitioned);
+        assert!(stored.partition_key.is_none());
+        assert_eq!(jar.cookie_header_for_context(&at_example).as_deref(), Some("id=2"));
+    }
+
+    #[test]
+    fn test_domain_limit_priority() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_max_cookies_per_domain(Some(3));
+
+        jar.store("a=1; Priority=Low", "https://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("b=1; Priority=High", "https://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("c=1; Priority=Low", "https://www.example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("other=1", "https://other.com/").unwrap();
+        jar.store("d=1", "https://api.example.com/").unwrap();
+
+        // The least recently accessed low priority cookie is removed
+        assert_eq!(jar.len(), 4);
+        assert!(jar.get("a", "example.com", "/").is_none());
+
+        clock.advance(Duration::from_secs(1));
+        jar.store("e=1", "https://example.com/").unwrap();
+        assert!(jar.get("c", "www.example.com", "/").is_none());
+
+        clock.advance(Duration::from_secs(1));
+        jar.store("f=1; Priority=High", "https://example.com/").unwrap();
+        assert!(jar.get("d", "api.example.com", "/").is_none());
+        assert!(jar.get("b", "example.com", "/").is_some());
+        assert!(jar.get("other", "other.com", "/").is_some());
+    }
+
+    #[test]
+    fn test_domain_limit_expired_and_access() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_max_cookies_per_domain(Some(2));
+
+        jar.store("a=1; Max-Age=10", "https://example.com/").unwrap();
+        jar.store("b=1; Max-Age=10", "https://example.com/").unwrap();
+
+        // Expired cookies are removed, even if not needed to meet the limit
+        clock.advance(Duration::from_secs(10));
+        jar.store("c=1", "https://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("d=1", "https://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("e=1", "https://other.example.com/").unwrap();
+        assert_eq!(jar.len(), 2);
+        assert!(jar.get("c", "example.com", "/").is_none());
+
+        // Accessed cookies are kept
+        clock.advance(Duration::from_secs(1));
+        jar.cookie_header_for("https://example.com/").unwrap();
+        jar.store("f=1", "https://example.com/").unwrap();
+        assert!(jar.get("d", "example.com", "/").is_some());
+        assert!(jar.get("e", "other.example.com", "/").is_none());
+
+        jar.set_max_cookies_per_domain(None);
+        jar.store("g=1", "https://example.com/").unwrap();
+        assert_eq!(jar.len(), 3);
+    }
+
+    #[test]
+    fn test_limits_cookie_size() {
+        let mut jar = CookieJar::new();
+        jar.set_limits(JarLimits { max_cookie_size: Some(20), ..JarLimits::unlimited() });
+
+        // "id" + "1" + "example.com" + "/"
+        assert!(jar.store("id=1", "https://example.com/").unwrap());
+        assert_eq!(jar.get("id", "example.com", "/").unwrap().size(), 15);
+
+        // Oversized cookies do not replace the stored one
+        assert!(!jar.store("id=0123456789", "https://example.com/").unwrap());
+        assert_eq!(jar.get("id", "example.com", "/").unwrap().cookie.value, "1");
+        assert!(jar.take_evictions().is_empty());
+    }
+
+    #[test]
+    fn test_limits_total() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_limits(JarLimits { max_cookies: Some(3), ..JarLimits::unlimited() });
+
+        jar.store("a=1; Max-Age=10", "https://a.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("b=1; Priority=High", "https://b.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("c=1", "https://c.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.cookie_header_for("https://b.com/").unwrap();
+
+        // Priority is not used by the total limit
+        jar.store("d=1", "https://d.com/").unwrap();
+        let evictions = jar.take_evictions();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].cookie.name(), "a");
+        assert_eq!(evictions[0].reason, EvictionReason::TotalLimit);
+
+        clock.advance(Duration::from_secs(1));
+        jar.store("e=1", "https://e.com/").unwrap();
+        let evictions = jar.take_evictions();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].cookie.name(), "c");
+        assert!(jar.take_evictions().is_empty());
+        assert_eq!(jar.len(), 3);
+    }
+
+    #[test]
+    fn test_limits_total_size() {
+        let (mut jar, clock) = jar_with_clock();
+        // Each cookie takes 7 bytes
+        jar.set_limits(JarLimits { max_total_size: Some(20), ..JarLimits::unlimited() });
+
+        jar.store("a=1; Max-Age=10", "https://a.co/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("b=1", "https://b.co/").unwrap();
+        clock.advance(Duration::from_secs(10));
+        jar.store("c=1", "https://c.co/").unwrap();
+
+        // The expired cookie is removed even if the size limit is met
+        let evictions = jar.take_evictions();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].cookie.name(), "a");
+        assert_eq!(evictions[0].reason, EvictionReason::Expired);
+
+        clock.advance(Duration::from_secs(1));
+        jar.store("d=1", "https://d.co/").unwrap();
+        let evictions = jar.take_evictions();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].cookie.name(), "b");
+        assert_eq!(evictions[0].reason, EvictionReason::TotalSizeLimit);
+        assert_eq!(jar.len(), 2);
+    }
+
+    #[test]
+    fn test_limits_evict_new_cookie() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_max_cookies_per_domain(Some(1));
+
+        assert!(jar.store("a=1; Priority=High", "https://example.com/").unwrap());
+        clock.advance(Duration::from_secs(1));
+        assert!(!jar.store("b=1; Priority=Low", "https://example.com/").unwrap());
+
+        assert_eq!(jar.len(), 1);
+        assert!(jar.get("a", "example.com", "/").is_some());
+        let evictions = jar.take_evictions();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].cookie.name(), "b");
+    }
+
+    #[test]
+    fn test_limits_domain_events() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_limits(JarLimits { max_cookies_per_domain: Some(1), ..JarLimits::default() });
+        assert_eq!(jar.max_cookies_per_domain(), Some(1));
+
+        jar.store("a=1", "https://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("b=1", "https://www.example.com/").unwrap();
+
+        let evictions = jar.take_evictions();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].cookie.name(), "a");
+        assert_eq!(evictions[0].reason, EvictionReason::DomainLimit);
+    }
+
+    #[test]
+    fn test_secure_from_insecure_origin() {
+        let mut jar = CookieJar::new();
+
+        assert!(!jar.store("id=1; Secure", "http://example.com/").unwrap());
+        assert!(!jar.store("__Secure-id=1; Secure", "http://example.com/").unwrap());
+        assert!(jar.store("id=1; Secure", "https://example.com/").unwrap());
+        assert!(jar.store("id=1; Secure", "wss://example.com/").unwrap());
+        assert_eq!(jar.len(), 1);
+    }
+
+    #[test]
+    fn test_secure_cookie_tossing() {
+        let mut jar = CookieJar::new();
+        assert!(jar.store("session=abc; Secure; Domain=example.com; Path=/", "https://www.example.com/").unwrap());
+
+        // An http subdomain cannot overwrite, shadow nor delete the secure cookie
+        assert!(!jar.store("session=evil; Domain=example.com", "http://evil.example.com/").unwrap());
+        assert!(!jar.store("session=evil", "http://evil.example.com/").unwrap());
+        assert!(!jar.store("session=evil; Path=/app", "http://www.example.com/").unwrap());
+        assert!(!jar.store("session=; Domain=example.com; Max-Age=0", "http://example.com/").unwrap());
+
+        assert_eq!(jar.len(), 1);
+        assert_eq!(jar.get("session", "example.com", "/").unwrap().cookie.value, "abc");
+        assert_eq!(jar.cookie_header_for("https://www.example.com/app").unwrap().as_deref(), Some("session=abc"));
+
+        // Other names and unrelated domains are not affected
+        assert!(jar.store("lang=en; Domain=example.com", "http://evil.example.com/").unwrap());
+        assert!(jar.store("session=1", "http://example.org/").unwrap());
+
+        // Secure origins can replace it
+        assert!(jar.store("session=def; Domain=example.com", "https://www.example.com/").unwrap());
+        assert!(!jar.get("session", "example.com", "/").unwrap().cookie.secure);
+        assert!(jar.store("session=evil; Domain=example.com", "http://evil.example.com/").unwrap());
+    }
+
+    #[test]
+    fn test_secure_cookie_path_not_shadowed() {
+        let mut jar = CookieJar::new();
+        assert!(jar.store("session=abc; Secure; Path=/app", "https://example.com/").unwrap());
+
+        // The cookie path does not path-match the secure cookie path
+        assert!(jar.store("session=1; Path=/", "http://example.com/").unwrap());
+        assert!(jar.store("session=1; Path=/application", "http://example.com/").unwrap());
+        assert!(!jar.store("session=1; Path=/app/admin", "http://example.com/").unwrap());
+        assert_eq!(jar.len(), 3);
+    }
+
+    #[test]
+    fn test_source_origin() {
+        let mut jar = CookieJar::new();
+
+        jar.store("a=1", "https://example.com/").unwrap();
+        jar.store("b=1", "http://example.com:8080/").unwrap();
+        jar.store("c=1", "ftp://example.com/").unwrap();
+
+        let a = jar.get("a", "example.com", "/").unwrap();
+        assert_eq!((a.source_scheme.as_str(), a.source_port), ("https", Some(443)));
+        let b = jar.get("b", "example.com", "/").unwrap();
+        assert_eq!((b.source_scheme.as_str(), b.source_port), ("http", Some(8080)));
+        let c = jar.get("c", "example.com", "/").unwrap();
+        assert_eq!((c.source_scheme.as_str(), c.source_port), ("ftp", None));
+    }
+
+    #[test]
+    fn test_origin_bound() {
+        let mut jar = CookieJar::new();
+        jar.store("id=1; Domain=example.com", "http://www.example.com/").unwrap();
+        jar.store("ws=1", "wss://example.com:8443/").unwrap();
+
+        // Disabled by default
+        assert_eq!(jar.cookie_header_for("https://example.com/").unwrap().as_deref(), Some("id=1; ws=1"));
+
+        jar.set_policy(CookiePolicy { origin_bound: true, ..CookiePolicy::default() });
+        assert!(jar.policy().origin_bound);
+
+        assert_eq!(jar.cookie_header_for("http://api.example.com/").unwrap().as_deref(), Some("id=1"));
+        assert_eq!(jar.cookie_header_for("http://example.com:80/").unwrap().as_deref(), Some("id=1"));
+        assert!(jar.cookie_header_for("https://example.com/").unwrap().is_none());
+        assert!(jar.cookie_header_for("http://example.com:8080/").unwrap().is_none());
+
+        assert_eq!(jar.cookie_header_for("https://example.com:8443/").unwrap().as_deref(), Some("ws=1"));
+        assert_eq!(jar.cookie_header_for("wss://example.com:8443/").unwrap().as_deref(), Some("ws=1"));
+    }
+
+    #[test]
+    fn test_schemeful_same_site() {
+        let mut jar = CookieJar::new();
+        jar.store("id=1; SameSite=Strict", "https://example.com/").unwrap();
+
+        // Link from the http version of the site
+        let mut context = RequestContext::new("https://example.com/").unwrap();
+        context.initiator = Some(RequestUrl::from_str("http://example.com/").unwrap());
+        assert_eq!(jar.cookie_header_for_context(&context).as_deref(), Some("id=1"));
+
+        jar.set_policy(CookiePolicy { schemeful_same_site: true, ..CookiePolicy::default() });
+        assert!(jar.cookie_header_for_context(&context).is_none());
+
+        context.initiator = Some(RequestUrl::from_str("https://www.example.com/").unwrap());
+        assert_eq!(jar.cookie_header_for_context(&context).as_deref(), Some("id=1"));
+    }
+
+    #[test]
+    fn test_lax_allowing_unsafe() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_policy(CookiePolicy { lax_allowing_unsafe: Some(LAX_ALLOWING_UNSAFE_MAX_AGE), ..CookiePolicy::default() });
+
+        jar.store("id=1", "https://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("lax=1; SameSite=Lax", "https://example.com/").unwrap();
+
+        // Form at other site posted to example.com
+        let mut context = RequestContext::new("https://example.com/").unwrap();
+        context.site_for_cookies = Some(RequestUrl::from_str("https://other.org/").unwrap());
+        context.method = String::from("POST");
+
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(jar.cookie_header_for_context(&context).as_deref(), Some("id=1"));
+
+        // The creation time is kept when the cookie is replaced
+        jar.store("id=2", "https://example.com/").unwrap();
+        clock.advance(Duration::from_secs(61));
+        assert!(jar.cookie_header_for_context(&context).is_none());
+
+        context.method = String::from("GET");
+        assert_eq!(jar.cookie_header_for_context(&context).as_deref(), Some("id=2; lax=1"));
+    }
+
+    #[test]
+    fn test_non_ascii_path() {
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store("p=1; Path=/é", "http://example.com/").unwrap());
+        assert!(jar.cookie_header_for("http://example.com/éa").unwrap().is_none());
+        assert_eq!(jar.cookie_header_for("http://example.com/é/a").unwrap().as_deref(), Some("p=1"));
+        assert_eq!(jar.cookie_header_for("http://example.com/é").unwrap().as_deref(), Some("p=1"));
+    }
+
+    #[test]
+    fn test_restore() {
+        let (mut jar, clock) = jar_with_clock();
+        jar.set_partitioned_storage(true);
+
+        jar.store("h=1; Max-Age=3600", "https://www.example.com/").unwrap();
+        jar.store_for_context("p=1; Secure; SameSite=None; Partitioned", &embedded("https://widget.org/", "https://example.com/")).unwrap();
+        clock.advance(Duration::from_secs(60));
+        jar.cookie_header_for("https://www.example.com/").unwrap();
+
+        let (mut restored, restored_clock) = jar_with_clock();
+        restored_clock.advance(Duration::from_secs(120));
+        restored.set_partitioned_storage(true);
+        for stored in jar.iter().cloned() {
+            assert!(restored.restore(stored).is_none());
+        }
+
+        let h = restored.get("h", "www.example.com", "/").unwrap();
+        let original = jar.get("h", "www.example.com", "/").unwrap();
+        assert!(h.cookie.host_only);
+        assert!(h.persistent);
+        assert_eq!(h.creation_time, original.creation_time);
+        assert_eq!(h.last_access_time, original.last_access_time);
+        assert_eq!(h.cookie.expire_time(), original.cookie.expire_time());
+        assert!(restored.get_partitioned("https://example.com", "p", "widget.org", "/").is_some());
+
+        // Host-only cookies are not sent to subdomains
+        assert!(restored.cookie_header_for("https://api.www.example.com/").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_matches_at() {
+        let (mut jar, clock) = jar_with_clock();
+        let policy = CookiePolicy { lax_allowing_unsafe: Some(LAX_ALLOWING_UNSAFE_MAX_AGE), ..CookiePolicy::default() };
+        jar.store("id=1; Max-Age=600", "https://example.com/").unwrap();
+
+        let mut context = RequestContext::new("https://example.com/").unwrap();
+        context.site_for_cookies = Some(RequestUrl::from_str("https://other.org/").unwrap());
+        context.method = String::from("POST");
+
+        let stored = jar.get("id", "example.com", "/").unwrap();
+        let now = clock.now();
+        assert!(stored.matches_at(&context, &policy, now + Duration::from_secs(120)));
+        assert!(!stored.matches_at(&context, &policy, now + Duration::from_secs(121)));
+
+        context.method = String::from("GET");
+        assert!(stored.matches_at(&context, &policy, now + Duration::from_secs(599)));
+        assert!(!stored.matches_at(&context, &policy, now + Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_store_max_age_deletes() {
+        let mut jar = CookieJar::new();
+
+        jar.store("a=1", "http://example.com/").unwrap();
+        jar.store("b=1; Max-Age=100", "http://example.com/").unwrap();
+        assert!(!jar.store("a=1; Max-Age=0", "http://example.com/").unwrap());
+        assert!(!jar.store("b=1; Max-Age=-1", "http://example.com/").unwrap());
+
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn test_store_session() {
+        let mut jar = CookieJar::new();
+
+        jar.store("a=1", "http://example.com/").unwrap();
+        jar.store("b=1; Max-Age=100", "http://example.com/").unwrap();
+        jar.remove_session_cookies();
+
+        assert_eq!(jar.len(), 1);
+        assert!(jar.get("b", "example.com", "/").is_some());
+    }
+
+    #[test]
+    fn test_header_order() {
+        let (mut jar, clock) = jar_with_clock();
+
+        jar.store("a=1", "http://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("b=2; Path=/app/admin", "http://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("c=3", "http://example.com/").unwrap();
+        clock.advance(Duration::from_secs(1));
+        jar.store("d=4; Path=/app", "http://example.com/").unwrap();
+
+        let header = jar.cookie_header_for("http://example.com/app/admin/users").unwrap();
+        assert_eq!(header.as_deref(), Some("b=2; d=4; a=1; c=3"));
+
+        let header = jar.cookie_header_for("http://example.com/application").unwrap();
+        assert_eq!(header.as_deref(), Some("a=1; c=3"));
+    }
+
+    #[test]
+    fn test_header_host_only() {
+        let mut jar = CookieJar::new();
+
+        jar.store("a=1", "http://example.com/").unwrap();
+        jar.store("b=2; Domain=example.com", "http://example.com/").unwrap();
+
+        let header = jar.cookie_header_for("http://www.example.com/").unwrap();
+        assert_eq!(header.as_deref(), Some("b=2"));
+    }
+
+    #[test]
+    fn test_header_secure() {
+        let mut jar = CookieJar::new();
+
+        jar.store("a=1; Secure", "https://example.com/").unwrap();
+
+        assert!(jar.cookie_header_for("http://example.com/").unwrap().is_none());
+        assert_eq!(jar.cookie_header_for("https://example.com/").unwrap().as_deref(), Some("a=1"));
+    }
+
+    #[test]
+    fn test_header_last_access() {
+        let (mut jar, clock) = jar_with_clock();
+
+        jar.store("a=1", "http://example.com/").unwrap();
+        jar.store("b=1", "http://other.com/").unwrap();
+        let stored_a = jar.get("a", "example.com", "/").unwrap().last_access_time;
+        let stored_b = jar.get("b", "other.com", "/").unwrap().last_access_time;
+
+        clock.advance(Duration::from_secs(2));
+        jar.cookie_header_for("http://example.com/").unwrap();
+
+        assert_eq!(jar.get("a", "example.com", "/").unwrap().last_access_time, stored_a + Duration::from_secs(2));
+        assert_eq!(jar.get("b", "other.com", "/").unwrap().last_access_time, stored_b);
+    }
+
+    #[test]
+    fn test_store_default_path() {
+        let mut jar = CookieJar::new();
+
+        jar.store("id=1", "http://example.com/app/login").unwrap();
+        jar.store("lang=en; Path=relative", "http://example.com/app/settings/lang").unwrap();
+
+        assert!(jar.get("id", "example.com", "/app").is_some());
+        assert!(jar.get("lang", "example.com", "/app/settings").is_some());
+
+        assert!(jar.cookie_header_for("http://example.com/admin").unwrap().is_none());
+        assert_eq!(jar.cookie_header_for("http://example.com/app/index").unwrap().as_deref(), Some("id=1"));
+        assert_eq!(jar.cookie_header_for("http://example.com/app/settings/").unwrap().as_deref(), Some("lang=en; id=1"));
+    }
+
+    #[test]
+    fn test_store_lenient() {
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store("id=1; Max-Age=abc; Domain; Path=/app", "http://example.com/").unwrap());
+
+        let stored = jar.get("id", "example.com", "/app").unwrap();
+        assert!(stored.cookie.host_only);
+        assert!(!stored.persistent);
+    }
+
+    #[test]
+    fn test_store_wrong() {
+        let mut jar = CookieJar::new();
+
+        assert!(jar.store("id", "http://example.com/").is_err());
+        assert!(jar.store("=1", "http://example.com/").is_err());
+        assert!(jar.store("id=1", "example.com").is_err());
+    }
+}
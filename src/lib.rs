@@ -62,26 +62,35 @@
 //! 
 //! ```
 //! 
-//! Note, if the `Set-Cookie` value does not include the `Domain` directive, it should be set the request's host domain:
+//! Note, if the `Set-Cookie` value does not include the `Domain` directive, the cookie is only sent to the host of
+//! the request (it is a host-only cookie). Use [SetCookie::from_response](crate::SetCookie::from_response) to parse the
+//! cookie with the request URL. It also rejects cookies whose `Domain` does not match the request host:
 //!
 //! ```rust
 //! use wcookie::SetCookie;
-//! use std::str::FromStr;
 //! 
-//! let mut cookie = SetCookie::from_str("cookie1=122343").unwrap();
+//! let cookie = SetCookie::from_response("cookie1=122343", "https://b.a/").unwrap();
 //!
-//! cookie.domain = Some(String::from("b.a"));
+//! assert!(cookie.host_only);
+//! assert!(cookie.use_in_request("b.a", "/", true));
+//! assert!(!cookie.use_in_request("c.b.a", "/", true));
 //! 
-//! assert!(cookie.use_in_request("c.b.a", "/", true));
+//! assert!(SetCookie::from_response("cookie1=122343; Domain=x.a", "https://b.a/").is_err());
 //! ```
 //! 
-//! By default, the cookie path, if not set, is `/`.
+//! By default, the cookie path, if not set, is `/`. `SetCookie::from_response` sets the path to the
+//! [default_path] of the request, the directory of the request path.
 //! 
 //! Note, `use_in_request` makes use of next functions:
 //! 
 //! * [SetCookie::expired](crate::SetCookie::expired)
 //! * [SetCookie::use_in_request_domain](crate::SetCookie::use_in_request_domain)
 //! * [SetCookie::use_in_request_path](crate::SetCookie::use_in_request_path)   
+//! * [SetCookie::same_site_allows](crate::SetCookie::same_site_allows)
+//! 
+//! `use_in_request` considers the request a same-site navigation. Cross-site requests are checked with
+//! [SetCookie::use_in_context](crate::SetCookie::use_in_context) and a [RequestContext]. Newer browser behaviors,
+//! such as schemeful same-site, are enabled with a [CookiePolicy].
 //! 
 //! A `SetCookie` can be converted into a [Cookie] to be incluided in a `Cookie` header:
 //! 
@@ -102,6 +111,22 @@
 //! 
 //! ```
 //! 
+//! ### Cookie jar
+//! 
+//! A [CookieJar] stores the cookies received from servers following the
+//! [RFC6265 storage model](https://datatracker.ietf.org/doc/html/rfc6265#section-5.3). Each `Set-Cookie`
+//! value is stored along with the URL of the request:
+//! 
+//! ```rust
+//! use wcookie::CookieJar;
+//! 
+//! let mut jar = CookieJar::new();
+//! 
+//! jar.store("id=1213342", "https://www.example.com/login").unwrap();
+//! 
+//! assert!(jar.get("id", "www.example.com", "/").unwrap().cookie.host_only);
+//! ```
+//! 
 //!  ## Server-side: creating `Set-Cookie` 
 //! 
 //! At server side, a cookie can be created using the `new` constructor and member values can be set when it is mutable:
@@ -116,26 +141,42 @@
 //! 
 //! ```
 //! 
+//! ## Server-side: receiving `Cookie` headers
+//! 
+//! The value of a `Cookie` request header, which may include several cookies, is parsed into a [CookieHeader]:
+//! 
+//! ```rust
+//! use wcookie::CookieHeader;
+//! use std::str::FromStr;
+//! 
+//! let header = CookieHeader::from_str("id=1213342; lang=en").unwrap();
+//! 
+//! assert_eq!(header.get("lang").unwrap().value, "en");
+//! ```
+//! 
 
 #![allow(dead_code)]
+#![allow(clippy::needless_return, clippy::len_zero)]
 
 #[macro_use]
 extern crate lazy_static;
 
 use std::fmt::Display;
-use chrono::Timelike;
 use std::fmt;
 use chrono::Utc;
-use chrono::{DateTime, Datelike};
+use chrono::DateTime;
 use std::str::FromStr;
-use std::error::Error;
 use std::time::{Duration, SystemTime};
 use std::ops::Add;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::cmp::{PartialEq, Eq};
 use std::hash::{Hash, Hasher};
 
 
+mod error;
+pub use error::{ParseError, ParseErrorKind, ParseWarning, ParseWarningKind};
+
 mod rfc_1123;
 pub use rfc_1123::parse_rfc_1123_date;
 
@@ -146,6 +187,46 @@ pub use rfc_850::parse_rfc_850_date;
 mod asct;
 pub use asct::parse_asct_date;
 
+mod http_date;
+pub use http_date::{HttpDate, parse_http_date};
+
+mod cookie_date;
+pub use cookie_date::parse_cookie_date;
+
+mod clock;
+pub use clock::{Clock, ManualClock, SystemClock};
+
+mod prefix;
+pub use prefix::CookiePrefix;
+
+mod domain;
+pub use domain::{domain_match, is_ip_address};
+
+mod public_suffix;
+pub use public_suffix::{PublicSuffixList, registrable_domain, is_public_suffix};
+
+mod url;
+pub use url::RequestUrl;
+
+mod context;
+pub use context::{RequestContext, RequestKind};
+
+mod validate;
+pub use validate::ValidationError;
+use validate::{is_attribute_value, is_cookie_value, is_domain, is_reserved_attribute, is_token};
+
+mod policy;
+pub use policy::{CookiePolicy, LAX_ALLOWING_UNSAFE_MAX_AGE};
+
+mod jar;
+pub use jar::{CookieJar, StoredCookie, DEFAULT_MAX_LIFETIME};
+
+mod limits;
+pub use limits::{EvictionEvent, EvictionReason, JarLimits, DEFAULT_MAX_COOKIE_SIZE, DEFAULT_MAX_COOKIES_PER_DOMAIN, DEFAULT_MAX_COOKIES};
+
+mod cookie_header;
+pub use cookie_header::CookieHeader;
+
 
 pub(crate) const COOKIE: &str = "cookie";
 pub(crate) const COOKIE_EXPIRES: &str = "expires";
@@ -158,32 +239,14 @@ pub(crate) const COOKIE_SAME_SITE_LAX: &str = "lax";
 pub(crate) const COOKIE_SAME_SITE_NONE: &str = "none";
 pub(crate) const COOKIE_SECURE: &str = "secure";
 pub(crate) const COOKIE_HTTP_ONLY: &str = "httponly";
+pub(crate) const COOKIE_PARTITIONED: &str = "partitioned";
+pub(crate) const COOKIE_PRIORITY: &str = "priority";
+pub(crate) const COOKIE_PRIORITY_LOW: &str = "low";
+pub(crate) const COOKIE_PRIORITY_MEDIUM: &str = "medium";
+pub(crate) const COOKIE_PRIORITY_HIGH: &str = "high";
 
-/// Error type produced while parsing a `Cookie`
-#[derive(Debug)]
-pub struct ParseError {
-    details: String
-}
-
-impl ParseError {
-    /// Constructor with any type of string
-    fn new<S>(msg: S) -> ParseError
-    where S: Into<String> {
-        ParseError{details: msg.into()}
-    }
-}
-
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,"{}",self.details)
-    }
-}
-
-impl Error for ParseError {
-    fn description(&self) -> &str {
-        &self.details
-    }
-}
+/// Seconds from the UNIX epoch to `Fri, 31 Dec 9999 23:59:59 GMT`, the latest expire time
+pub(crate) const MAX_EXPIRE_SECS: u64 = 253_402_300_799;
 
 /// Represents a cookie sent at a `Cookie` header at an HTTP Request.
 #[derive(Debug, PartialEq,Eq)]
@@ -205,6 +268,20 @@ impl Cookie {
     }
 }
 
+impl Cookie {
+    /// Checks the cookie can be safely written in a `Cookie` header: the name is a token and
+    /// the value has only `cookie-octet` characters. See [SetCookie::validate].
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !is_token(&self.name) {
+            return Err(ValidationError::InvalidName(self.name.clone()));
+        }
+        if !is_cookie_value(&self.value) {
+            return Err(ValidationError::InvalidValue(self.value.clone()));
+        }
+        return Ok(());
+    }
+}
+
 impl Display for Cookie {
     /// Writes a cookie with format `name=value`
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> { 
@@ -227,9 +304,18 @@ impl FromStr for Cookie {
     }
 }
 
-/// Enum with `SameSite` possible values for `Set-Cookie` attribute
-#[derive(Debug,Copy,Clone,PartialEq,Eq)]
-pub enum SameSiteValue {Strict, Lax, None}
+/// Enum with `SameSite` possible values for `Set-Cookie` attribute.
+///
+/// `Unspecified` is used when the cookie has not got a valid `SameSite` attribute. Browsers handle these cookies
+/// as set by [CookiePolicy::default_same_site], usually as `Lax`, see [SetCookie::same_site_allows].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum SameSiteValue {
+    Strict,
+    Lax,
+    None,
+    #[default]
+    Unspecified
+}
 
 impl FromStr for SameSiteValue {
     type Err = ParseError;
@@ -240,9 +326,46 @@ impl FromStr for SameSiteValue {
             COOKIE_SAME_SITE_LAX => Ok(SameSiteValue::Lax),
             COOKIE_SAME_SITE_NONE => Ok(SameSiteValue::None),
             _ => Err(
-                ParseError::new(format!("Invalid SameSite cookie directive value: {}", s)))
+                ParseError::new(ParseErrorKind::InvalidSameSite, format!("Invalid SameSite cookie directive value: {}", s)))
+        }
+
+    }
+}
+
+/// `Priority` attribute values. When a [CookieJar] has too many cookies for a domain, cookies with lower
+/// priority are removed first, see [CookieJar::set_max_cookies_per_domain].
+/// 
+/// Priorities are ordered, `Low` is lower than `Medium` and `Medium` is lower than `High`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CookiePriority {
+    Low,
+    #[default]
+    Medium,
+    High
+}
+
+impl FromStr for CookiePriority {
+    type Err = ParseError;
+
+    /// Parses a priority, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s.to_ascii_lowercase().as_str() {
+            COOKIE_PRIORITY_LOW => Ok(CookiePriority::Low),
+            COOKIE_PRIORITY_MEDIUM => Ok(CookiePriority::Medium),
+            COOKIE_PRIORITY_HIGH => Ok(CookiePriority::High),
+            _ => Err(
+                ParseError::new(ParseErrorKind::InvalidPriority, format!("Invalid Priority cookie directive value: {}", s)))
         }
+    }
+}
 
+impl Display for CookiePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CookiePriority::Low => write!(f, "Low"),
+            CookiePriority::Medium => write!(f, "Medium"),
+            CookiePriority::High => write!(f, "High")
+        }
     }
 }
 
@@ -278,24 +401,33 @@ pub struct SetCookie {
     pub name: String,
     /// Cookie value
     pub value: String,
-    /// Cookie domain, by default is the originating domain of the request
+    /// Cookie domain, by default is the originating domain of the request. Parsing removes one leading dot
+    /// from the `Domain` attribute, as defined in [RFC6265 Section 5.2.3](https://datatracker.ietf.org/doc/html/rfc6265#section-5.2.3).
     pub domain: Option<String>,
+    /// The cookie has not got a `Domain` attribute, so `domain` is the request host and the cookie
+    /// is only sent to that host, not to its subdomains. See [SetCookie::from_response].
+    pub host_only: bool,
     /// Cookie path, by default, it is the request's path
     pub path: Option<String>,
     /// When the Cookie expires, if None, it does not expire.
     /// This value is obtained from Max-Age and Expires attributes (Max-Age has precedence)
     pub expires: Option<DateTime<Utc>>,
-    /// Max-age
+    /// Max-age. Zero or negative `Max-Age` values are stored as a zero duration, as both expire the cookie
+    /// at once, so `Max-Age=-1` is written back as `Max-Age=0`.
     pub max_age: Option<Duration>,
     /// Time there the cookie was received/create to calculate later `expire_time`
     pub(crate) created: SystemTime,
-    /// Internal expires time from expires and max_age
-    /// Cookie same site value (option)
+    /// Cookie same site value, `Unspecified` if the cookie has not got the attribute
     pub same_site: SameSiteValue,
     /// Cookie requires HTTPS
     pub secure: bool,
     /// Browsers does not allow Javascript access to this cookie
     pub http_only: bool,
+    /// The cookie is stored in a separate jar for each top-level site (CHIPS), see
+    /// [CookieJar::set_partitioned_storage]. It requires `Secure`.
+    pub partitioned: bool,
+    /// Cookie priority, `Medium` by default
+    pub priority: CookiePriority,
     /// Other Set-Cookie extensions
     pub extensions: HashMap<String, Option<String>>    
 }
@@ -309,17 +441,155 @@ impl SetCookie {
             name: name.into(),
             value: value.into(),
             domain: None,
+            host_only: false,
             path: None,
             expires: None,
             max_age: None,
             created: SystemTime::now(),            
-            same_site: SameSiteValue::Lax,
+            same_site: SameSiteValue::Unspecified,
             secure: false,
             http_only: false,
+            partitioned: false,
+            priority: CookiePriority::Medium,
             extensions: HashMap::new()
         }
     }
 
+    /// Creates a cookie with the `__Host-` prefix, see [CookiePrefix::Host]: the name is prefixed if needed and the
+    /// cookie is `Secure`, without `Domain` and with `/` path. It is only sent to the host that set it, with HTTPS.
+    /// 
+    /// ```rust
+    /// use wcookie::SetCookie;
+    /// 
+    /// let cookie = SetCookie::new_host_prefixed("session", "a3fWa");
+    /// 
+    /// assert_eq!(cookie.name, "__Host-session");
+    /// assert_eq!(cookie.to_string(), "__Host-session=a3fWa; Path=/; Secure");
+    /// assert!(cookie.satisfies_prefix());
+    /// ```
+    pub fn new_host_prefixed<S>(name: S, value: S) -> SetCookie 
+    where S : Into<String> {
+        let name = name.into();
+        let name = if CookiePrefix::of(&name) == Some(CookiePrefix::Host) {
+            name
+        } else {
+            format!("{}{}", CookiePrefix::Host, name)
+        };
+        let mut cookie = SetCookie::new(name, value.into());
+        cookie.path = Some(String::from("/"));
+        cookie.secure = true;
+        return cookie;
+    }
+
+    /// Gets the cookie name prefix, if any
+    pub fn prefix(&self) -> Option<CookiePrefix> {
+        CookiePrefix::of(&self.name)
+    }
+
+    /// Checks if the cookie attributes meet the requirements of its name prefix, see [CookiePrefix].
+    /// Cookies without prefix always meet them.
+    /// 
+    /// ```rust
+    /// use wcookie::SetCookie;
+    /// use std::str::FromStr;
+    /// 
+    /// assert!(SetCookie::from_str("__Secure-id=1; Secure").unwrap().satisfies_prefix());
+    /// assert!(!SetCookie::from_str("__Secure-id=1").unwrap().satisfies_prefix());
+    /// assert!(!SetCookie::from_str("__Host-id=1; Secure; Path=/; Domain=example.com").unwrap().satisfies_prefix());
+    /// ```
+    pub fn satisfies_prefix(&self) -> bool {
+        return match self.prefix() {
+            Some(CookiePrefix::Secure) => self.secure,
+            Some(CookiePrefix::Host) => self.secure && self.path.as_deref() == Some("/")
+                && (self.domain.is_none() || self.host_only),
+            None => true
+        };
+    }
+
+    /// Parses a `Set-Cookie` header value received in the response to a request to `request_url`.
+    /// 
+    /// The cookie is bound to the request as described at
+    /// [RFC6265 Section 5.3](https://datatracker.ietf.org/doc/html/rfc6265#section-5.3):
+    /// 
+    /// * If the cookie has not got a `Domain`, `domain` is set to the request host and the cookie is `host_only`.
+    /// * If the `Domain` does not domain-match the request host, the cookie is rejected.
+    /// * If the `Domain` is a public suffix (see [is_public_suffix]), the cookie is rejected unless the domain is the
+    ///   request host. In that case the cookie is `host_only`.
+    /// * If the cookie has not got a `Path`, or it does not start with `/`, `path` is set to the
+    ///   [default_path] of the request path.
+    /// * If the cookie name has a prefix and its attributes do not meet the prefix requirements,
+    ///   see [SetCookie::satisfies_prefix], the cookie is rejected.
+    /// * If the cookie is `Partitioned` but not `Secure`, the cookie is rejected.
+    /// 
+    /// ```rust
+    /// use wcookie::SetCookie;
+    /// 
+    /// let cookie = SetCookie::from_response("id=1; Domain=example.com", "https://www.example.com/").unwrap();
+    /// 
+    /// assert!(!cookie.host_only);
+    /// assert!(cookie.use_in_request_domain("api.example.com"));
+    /// 
+    /// assert!(SetCookie::from_response("id=1; Domain=com", "https://www.example.com/").is_err());
+    /// 
+    /// let cookie = SetCookie::from_response("id=1", "https://www.example.com/app/login").unwrap();
+    /// 
+    /// assert_eq!(cookie.path.as_deref(), Some("/app"));
+    /// ```
+    pub fn from_response(set_cookie: &str, request_url: &str) -> Result<SetCookie, ParseError> {
+        return SetCookie::from_response_with_list(set_cookie, request_url, &PublicSuffixList::bundled());
+    }
+
+    /// Parses a `Set-Cookie` header value received in the response to a request to `request_url`, as
+    /// [SetCookie::from_response] does, checking public suffixes with `public_suffix_list` instead of the bundled list
+    pub fn from_response_with_list(set_cookie: &str, request_url: &str, public_suffix_list: &PublicSuffixList) -> Result<SetCookie, ParseError> {
+        let url = RequestUrl::from_str(request_url)?;
+        let mut cookie = SetCookie::from_str(set_cookie)?;
+        cookie.bind_to_request(&url, public_suffix_list)?;
+        return Ok(cookie);
+    }
+
+    /// Sets the cookie `domain`, `host_only` and `path` for a cookie received from `url`
+    pub(crate) fn bind_to_request(&mut self, url: &RequestUrl, public_suffix_list: &PublicSuffixList) -> Result<(), ParseError> {
+        if !self.satisfies_prefix() {
+            return Err(ParseError::new(ParseErrorKind::InvalidPrefix, format!("Cookie {} does not meet its prefix requirements", self.name)));
+        }
+
+        if self.partitioned && !self.secure {
+            return Err(ParseError::new(ParseErrorKind::PartitionedNotSecure, format!("Partitioned cookie {} is not secure", self.name)));
+        }
+
+        // Parsing already removed the leading dot, so a domain that still starts with a dot does not domain-match
+        // the host, as RFC6265 Section 5.2.3 removes only one. Domains are compared in lowercase.
+        let domain = self.domain.take()
+            .map(|domain| domain.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if domain.starts_with('.') {
+            return Err(ParseError::new(ParseErrorKind::DomainMismatch, format!("Cookie domain {} does not match request host {}", domain, url.host)));
+        } else if domain.is_empty() {
+            self.domain = Some(url.host.clone());
+            self.host_only = true;
+        } else if public_suffix_list.is_public_suffix(&domain) {
+            if domain != url.host {
+                return Err(ParseError::new(ParseErrorKind::PublicSuffixDomain, format!("Cookie domain {} is a public suffix", domain)));
+            }
+            self.domain = Some(domain);
+            self.host_only = true;
+        } else {
+            if !domain_match(&url.host, &domain) {
+                return Err(ParseError::new(ParseErrorKind::DomainMismatch, format!("Cookie domain {} does not match request host {}", domain, url.host)));
+            }
+            self.domain = Some(domain);
+            self.host_only = false;
+        }
+
+        if !self.path.as_deref().unwrap_or_default().starts_with('/') {
+            self.path = Some(String::from(default_path(&url.path)));
+        }
+
+        return Ok(());
+    }
+
     /// Generates a [Cookie] to be used in an HTTP Request
     pub fn to_cookie (& self) -> Cookie {
         Cookie {
@@ -328,7 +598,9 @@ impl SetCookie {
         }
     }
 
-    /// Gets the cookie path or the detault value which is `\"/\"`
+    /// Gets the cookie path or the detault value which is `\"/\"`.
+    /// 
+    /// Note cookies parsed with [SetCookie::from_response] have the path set to the [default_path] of the request.
     pub fn path_or_default(&self) -> &str {
         self.path.as_deref().unwrap_or("/")
     }
@@ -338,31 +610,96 @@ impl SetCookie {
     /// 
     /// This value is get from `Expires` and `Max-Age`params. When both params are set, 
     /// `Max-Age` has precedence.
+    /// 
+    /// A zero `Max-Age` expires at the earliest time, the UNIX epoch, as defined in
+    /// [RFC6265 Section 5.2.2](https://datatracker.ietf.org/doc/html/rfc6265#section-5.2.2).
+    /// Expire times are never later than the end of year 9999, the last date that can be written at `Expires`.
     pub fn expire_time(&self) -> Option<SystemTime> {
+        let latest = SystemTime::UNIX_EPOCH.add(Duration::from_secs(MAX_EXPIRE_SECS));
         if let Some(duration) = self.max_age {
-            return Some(self.created.add(duration));
+            if duration.is_zero() {
+                return Some(SystemTime::UNIX_EPOCH);
+            }
+            return Some(self.created.checked_add(duration).map_or(latest, |time| time.min(latest)));
         }        
         if let Some(date) = self.expires {
             let time = date.timestamp();
             if let Ok(utime) = u64::try_from(time) {
-                return Some(SystemTime::UNIX_EPOCH.add(Duration::from_secs(utime)));
+                return Some(SystemTime::UNIX_EPOCH.add(Duration::from_secs(utime.min(MAX_EXPIRE_SECS))));
             } else { // Time before UNIX Epoch, it is expired
-                return Some(self.created.clone())
+                return Some(SystemTime::UNIX_EPOCH)
             }
         }
         return None
     }
 
+    /// Limits the cookie lifetime to `max` from the moment the cookie was parsed or created, as
+    /// [RFC6265bis Section 5.5](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.5)
+    /// user agents do with `Expires` and `Max-Age` values later than 400 days, see [DEFAULT_MAX_LIFETIME].
+    /// 
+    /// If the cookie expires later, `max_age` is set to `max`. Returns `true` if the lifetime was capped.
+    /// 
+    /// ```rust
+    /// use wcookie::{DEFAULT_MAX_LIFETIME, SetCookie};
+    /// use std::str::FromStr;
+    /// use std::time::Duration;
+    /// 
+    /// let mut cookie = SetCookie::from_str("id=1; Max-Age=315360000").unwrap();
+    /// 
+    /// assert!(cookie.cap_lifetime(DEFAULT_MAX_LIFETIME));
+    /// assert_eq!(cookie.max_age, Some(Duration::from_secs(400 * 24 * 3600)));
+    /// ```
+    pub fn cap_lifetime(&mut self, max: Duration) -> bool {
+        let latest = match self.created.checked_add(max) {
+            Some(latest) => latest,
+            None => return false
+        };
+        return match self.expire_time() {
+            Some(expires) if expires > latest => {
+                self.max_age = Some(max);
+                true
+            },
+            _ => false
+        };
+    }
+
     /// Checks if the cookie is expired.
     /// 
-    /// `Max-Age` time is assumed from the moment the cookie was parsed or created
+    /// `Max-Age` time is assumed from the moment the cookie was parsed or created. A cookie with
+    /// `Max-Age=0` or a negative value is always expired.
     pub fn expired(&self) -> bool {
+        return self.expired_at(SystemTime::now());
+    }
+
+    /// Checks if the cookie is expired at the time `now`, see [SetCookie::expired]
+    /// 
+    /// ```rust
+    /// use wcookie::{ParseMode, SetCookie};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// 
+    /// let created = UNIX_EPOCH + Duration::from_secs(1_000_000);
+    /// let cookie = SetCookie::parse_at("id=1; Max-Age=60", ParseMode::Strict, created).unwrap();
+    /// 
+    /// assert!(!cookie.expired_at(created + Duration::from_secs(59)));
+    /// assert!(cookie.expired_at(created + Duration::from_secs(60)));
+    /// ```
+    pub fn expired_at(&self, now: SystemTime) -> bool {
         if let Some(expires) = self.expire_time() {
-            let now = SystemTime::now();
-            return expires < now;
+            return expires <= now;
         }
         return false;
     }
+
+    /// Gets the time the cookie was parsed or created, used as the start of `Max-Age`
+    pub fn created(&self) -> SystemTime {
+        self.created
+    }
+
+    /// Sets the time the cookie was created, for example, to restore a saved cookie. `Max-Age` is
+    /// counted from this time.
+    pub fn set_created(&mut self, created: SystemTime) {
+        self.created = created;
+    }
     
 
     /// Checks if the request path match the cookie path. 
@@ -387,9 +724,9 @@ impl SetCookie {
             // 1. They are identical, or 
             request_path_len == cookie_path_len 
             // 2. A and cookie path ends with an slash
-            || cookie_path.chars().nth(cookie_path_len - 1).unwrap() == '/' 
+            || cookie_path.ends_with('/')
             // 3. A and the first char of request path that is not incled in request path is an slash
-            || path.chars().nth(cookie_path_len).unwrap() == '/'; 
+            || path.as_bytes()[cookie_path_len] == b'/';
     }
 
     /// Checks if the cookie can be sent to the `request_domain`. 
@@ -399,57 +736,206 @@ impl SetCookie {
     /// > the user agent will include the cookie in the Cookie header when making HTTP requests 
     /// > to example.com, www.example.com, and www.corp.example.com.
     /// 
+    /// The domain is checked with the [RFC6265 Section 5.1.3](https://datatracker.ietf.org/doc/html/rfc6265#section-5.1.3)
+    /// domain-match algorithm, see [domain_match]. If the cookie domain is a public suffix, such as `com` or `co.uk`,
+    /// it is only used for the identical host (see [is_public_suffix]).
+    /// 
+    /// If the cookie is `host_only`, it is only used when `request_domain` is the cookie domain.
+    /// 
     /// Note: if field `domain` is not set, this function return always `false`
     pub fn use_in_request_domain(&self, request_domain: &str) -> bool {
-        if self.domain.is_none() {
-            return false;
-        }
+        return self.use_in_request_domain_with_list(request_domain, &PublicSuffixList::bundled());
+    }
 
-        let cookie_domain = self.domain.as_deref().unwrap();
-        if let Some(index) = request_domain.rfind(cookie_domain) {
-            if index == 0 { // same domain
-                return true;
+    /// Checks if the cookie can be sent to the `request_domain`, as [SetCookie::use_in_request_domain] does,
+    /// checking public suffixes with `public_suffix_list` instead of the bundled list
+    pub fn use_in_request_domain_with_list(&self, request_domain: &str, public_suffix_list: &PublicSuffixList) -> bool {
+        if let Some(cookie_domain) = self.domain.as_deref() {
+            if self.host_only {
+                return request_domain.eq_ignore_ascii_case(cookie_domain);
+            }
+            if public_suffix_list.is_public_suffix(cookie_domain) {
+                let cookie_domain = cookie_domain.strip_prefix('.').unwrap_or(cookie_domain);
+                return request_domain.eq_ignore_ascii_case(cookie_domain);
             }
-            // The cookie domain is a subdomain of request domain, acccept
-            return request_domain.chars().nth(index-1).unwrap() == '.';
+            return domain_match(request_domain, cookie_domain);
         }
-         
         return false;
     }
 
-    /// Checks if the cookie can be used on this request
-    pub fn use_in_request(&self, request_domain: &str, request_path: &str, secure: bool) -> bool {
+    /// Checks if the cookie `SameSite` attribute allows sending the cookie in the request `context`, as defined at
+    /// [RFC6265bis Section 5.8.3](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-5.8.3):
+    /// 
+    /// * Same-site requests (see [RequestContext::is_same_site]) include all cookies.
+    /// * Cross-site requests do not include `Strict` cookies.
+    /// * Cross-site requests only include `Lax` cookies at top-level navigations with a safe method, such as `GET`.
+    /// * Cookies without `SameSite` are handled as `Lax`, as browsers do by default. See [CookiePolicy::default_same_site].
+    /// 
+    /// `SameSite=None` cookies are only allowed if they are `Secure`, as browsers require.
+    pub fn same_site_allows(&self, context: &RequestContext) -> bool {
+        return self.same_site_allows_with_policy(context, &CookiePolicy::default());
+    }
+
+    /// Checks if the cookie `SameSite` attribute allows sending the cookie in the request `context`, as
+    /// [SetCookie::same_site_allows] does, with the browser behaviors enabled at `policy`:
+    ///
+    /// * [CookiePolicy::schemeful_same_site]: requests are only same-site if they also have the same scheme,
+    ///   see [RequestContext::is_schemeful_same_site].
+    /// * [CookiePolicy::default_same_site]: how cookies without `SameSite` are handled.
+    /// * [CookiePolicy::lax_allowing_unsafe]: cookies without `SameSite` handled as `Lax` are also sent at cross-site
+    ///   top-level navigations with unsafe methods, such as `POST`, while they are recent. The cookie age is counted
+    ///   from [SetCookie::created].
+    ///
+    /// ```rust
+    /// use wcookie::{CookiePolicy, RequestContext, RequestUrl, SetCookie, LAX_ALLOWING_UNSAFE_MAX_AGE};
+    /// use std::str::FromStr;
+    ///
+    /// let policy = CookiePolicy { lax_allowing_unsafe: Some(LAX_ALLOWING_UNSAFE_MAX_AGE), ..CookiePolicy::default() };
+    /// let cookie = SetCookie::from_str("id=1").unwrap();
+    ///
+    /// // Form at other site posted to example.com
+    /// let mut context = RequestContext::new("https://example.com/login").unwrap();
+    /// context.site_for_cookies = Some(RequestUrl::from_str("https://other.org/").unwrap());
+    /// context.method = String::from("POST");
+    ///
+    /// assert!(!cookie.same_site_allows(&context));
+    /// assert!(cookie.same_site_allows_with_policy(&context, &policy));
+    /// ```
+    pub fn same_site_allows_with_policy(&self, context: &RequestContext, policy: &CookiePolicy) -> bool {
+        return self.same_site_allows_at(context, policy, SystemTime::now());
+    }
+
+    /// Checks if the cookie `SameSite` attribute allows sending the cookie in the request `context` at the time `now`,
+    /// see [SetCookie::same_site_allows_with_policy]
+    pub fn same_site_allows_at(&self, context: &RequestContext, policy: &CookiePolicy, now: SystemTime) -> bool {
+        return self.same_site_allows_since(context, policy, self.created, now, &PublicSuffixList::bundled());
+    }
 
-        if self.domain.is_none() {
+    /// Checks if the cookie `SameSite` attribute allows sending the cookie in the request `context` at the time `now`,
+    /// see [SetCookie::same_site_allows_with_policy]. `created` is the time the cookie was first stored.
+    pub(crate) fn same_site_allows_since(&self, context: &RequestContext, policy: &CookiePolicy, created: SystemTime, now: SystemTime,
+        public_suffix_list: &PublicSuffixList) -> bool {
+        if self.same_site == SameSiteValue::None && !self.secure {
             return false;
         }
 
-        // Match Secure restrictions 
+        if context.is_same_site_with_policy(policy, public_suffix_list) {
+            return true;
+        }
+
+        let same_site = match self.same_site {
+            SameSiteValue::Unspecified => policy.default_same_site,
+            same_site => same_site
+        };
+
+        return match same_site {
+            SameSiteValue::Strict => false,
+            SameSiteValue::None => true,
+            SameSiteValue::Lax | SameSiteValue::Unspecified => {
+                if !context.is_navigation() {
+                    return false;
+                }
+                if context.is_safe_method() {
+                    return true;
+                }
+                // Lax-allowing-unsafe only applies to cookies without SameSite
+                return self.same_site == SameSiteValue::Unspecified && policy.lax_allowing_unsafe
+                    .is_some_and(|max_age| now.duration_since(created).is_ok_and(|age| age <= max_age));
+            }
+        };
+    }
+
+    /// Checks if the cookie can be used in a request with `context`:
+    /// 
+    /// * The cookie is not expired, see [SetCookie::expired]
+    /// * The request host matches the cookie domain, see [SetCookie::use_in_request_domain]
+    /// * The request path matches the cookie path, see [SetCookie::use_in_request_path]
+    /// * `Secure` cookies are only used with secure protocols, such as HTTPS
+    /// * `SameSite` allows the cookie, see [SetCookie::same_site_allows]
+    /// 
+    /// ```rust
+    /// use wcookie::{RequestContext, RequestKind, RequestUrl, SetCookie};
+    /// use std::str::FromStr;
+    /// 
+    /// let cookie = SetCookie::from_response("id=1; Domain=example.com; SameSite=Strict", "https://example.com/").unwrap();
+    /// 
+    /// let mut context = RequestContext::new("https://www.example.com/").unwrap();
+    /// assert!(cookie.use_in_context(&context));
+    /// 
+    /// // Link from other site
+    /// context.initiator = Some(RequestUrl::from_str("https://other.org/").unwrap());
+    /// assert!(!cookie.use_in_context(&context));
+    /// ```
+    pub fn use_in_context(&self, context: &RequestContext) -> bool {
+        return self.use_in_context_with_policy(context, &CookiePolicy::default());
+    }
+
+    /// Checks if the cookie can be used in a request with `context`, as [SetCookie::use_in_context] does, with
+    /// the browser behaviors enabled at `policy`, see [SetCookie::same_site_allows_with_policy].
+    ///
+    /// [CookiePolicy::origin_bound] is not checked, as the cookie does not know the origin that set it.
+    /// See [StoredCookie::matches_with_policy].
+    pub fn use_in_context_with_policy(&self, context: &RequestContext, policy: &CookiePolicy) -> bool {
+        return self.use_in_context_at(context, policy, SystemTime::now());
+    }
 
-        if self.secure && !secure {
+    /// Checks if the cookie can be used in a request with `context` at the time `now`, see
+    /// [SetCookie::use_in_context_with_policy]. Expiration and the Lax-allowing-unsafe window are checked at `now`,
+    /// for example, the time of a [Clock].
+    ///
+    /// ```rust
+    /// use wcookie::{CookiePolicy, ParseMode, RequestContext, SetCookie};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    /// let cookie = SetCookie::parse_at("id=1; Max-Age=60; Domain=example.com", ParseMode::Lenient, now).unwrap();
+    /// let context = RequestContext::new("https://example.com/").unwrap();
+    ///
+    /// assert!(cookie.use_in_context_at(&context, &CookiePolicy::default(), now));
+    /// assert!(!cookie.use_in_context_at(&context, &CookiePolicy::default(), now + Duration::from_secs(60)));
+    /// ```
+    pub fn use_in_context_at(&self, context: &RequestContext, policy: &CookiePolicy, now: SystemTime) -> bool {
+        if self.expired_at(now) {
             return false;
         }
-    
-        // Strict behaviour: it is only same-site if the domain is the same
 
-        if self.same_site == SameSiteValue::Strict && self.domain.as_deref().unwrap() != request_domain {
+        if !self.use_in_request_domain(&context.url.host) {
             return false;
         }
 
-        // Lax behaviour: allow cross-site from subdomain to father domain
-        if self.same_site == SameSiteValue::Lax && !self.use_in_request_domain(request_domain) {
+        if self.secure && !context.url.is_secure() {
             return false;
         }
 
-        // None: allow all cookies transfer but only it HTTPS is in use
-        if self.same_site == SameSiteValue::None && ! self.secure {
+        if !self.same_site_allows_at(context, policy, now) {
             return false;
         }
 
-        // PATH filteringseconds
+        return self.use_in_request_path(&context.url.path);
+    }
+
+    /// Checks if the cookie can be used on this request.
+    /// 
+    /// The request is considered a same-site top-level navigation, see [SetCookie::use_in_context] to
+    /// check cookies for cross-site requests.
+    pub fn use_in_request(&self, request_domain: &str, request_path: &str, secure: bool) -> bool {
+        let url = RequestUrl {
+            scheme: String::from(if secure { "https" } else { "http" }),
+            host: request_domain.to_ascii_lowercase(),
+            port: None,
+            path: String::from(request_path)
+        };
+
+        let context = RequestContext {
+            site_for_cookies: Some(url.clone()),
+            url,
+            initiator: None,
+            kind: RequestKind::Navigation,
+            method: String::from("GET")
+        };
 
-        return self.use_in_request_path(request_path);      
-    } 
+        return self.use_in_context(&context);
+    }
 }
 
 impl PartialEq for SetCookie {
@@ -463,47 +949,322 @@ impl PartialEq for SetCookie {
 
 impl Eq for SetCookie{}
 
+/// Mode used to parse `Set-Cookie` values, see [SetCookie::parse]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Any invalid attribute, such as `Max-Age=abc` or a `Domain` without value, is an error
+    #[default]
+    Strict,
+    /// User agent behaviour, as defined in [RFC6265 Section 5.2](https://datatracker.ietf.org/doc/html/rfc6265#section-5.2):
+    /// invalid attributes are ignored and the cookie is kept
+    Lenient
+}
+
+impl SetCookie {
+    /// Parses a `Set-Cookie` header value with `mode`.
+    /// 
+    /// At [ParseMode::Strict] mode, which is used by `from_str`, any invalid attribute is an error.
+    /// 
+    /// At [ParseMode::Lenient] mode, parsing follows the [RFC6265 Section 5.2](https://datatracker.ietf.org/doc/html/rfc6265#section-5.2)
+    /// algorithm, as user agents do:
+    /// 
+    /// * The cookie is only rejected if it has not got `=` or its name is empty. The value may be empty.
+    /// * Unknown attributes are kept at `extensions`.
+    /// * Invalid attributes, such as `Max-Age=abc`, an invalid `Expires` date or an empty `Domain`, are ignored.
+    /// * `Expires` dates are parsed with [parse_cookie_date], so variants such as `21-Oct-2015 07:28:00` are accepted.
+    /// * A `Path` that does not start with `/` is ignored, so the default path is used.
+    /// * An invalid `SameSite` value sets the default value.
+    /// * If an attribute appears several times, the last one wins.
+    /// 
+    /// ```rust
+    /// use wcookie::{ParseMode, SameSiteValue, SetCookie};
+    /// 
+    /// assert!(SetCookie::parse("id=1; Max-Age=abc; SameSite=Strict", ParseMode::Strict).is_err());
+    /// 
+    /// let cookie = SetCookie::parse("id=1; Max-Age=abc; SameSite=Strict; Domain", ParseMode::Lenient).unwrap();
+    /// 
+    /// assert_eq!(cookie.max_age, None);
+    /// assert_eq!(cookie.domain, None);
+    /// assert_eq!(cookie.same_site, SameSiteValue::Strict);
+    /// ```
+    pub fn parse(s: &str, mode: ParseMode) -> Result<SetCookie, ParseError> {
+        return SetCookie::parse_with_warnings(s, mode).map(|(cookie, _)| cookie);
+    }
+
+    /// Parses a `Set-Cookie` header value with `mode`, see [SetCookie::parse], received at the time `now`.
+    /// `Max-Age` is counted from `now`.
+    pub fn parse_at(s: &str, mode: ParseMode, now: SystemTime) -> Result<SetCookie, ParseError> {
+        return SetCookie::parse_with_warnings_at(s, mode, now).map(|(cookie, _)| cookie);
+    }
+
+    /// Parses a `Set-Cookie` header value with `mode`, see [SetCookie::parse], and gets the non-fatal issues
+    /// found at the attributes: duplicated attributes, attributes ignored at [ParseMode::Lenient] mode,
+    /// unknown attributes, an `Expires` date in the past or attributes that do not meet the name prefix requirements.
+    /// 
+    /// ```rust
+    /// use wcookie::{ParseMode, ParseWarningKind, SetCookie};
+    /// 
+    /// let input = "id=1; Path=/a; Max-Age=abc; Path=/b";
+    /// let (cookie, warnings) = SetCookie::parse_with_warnings(input, ParseMode::Lenient).unwrap();
+    /// 
+    /// assert_eq!(cookie.path.as_deref(), Some("/b"));
+    /// assert_eq!(warnings.len(), 2);
+    /// assert_eq!(warnings[0].kind, ParseWarningKind::IgnoredAttribute);
+    /// assert_eq!(&input[warnings[0].span.clone()], "Max-Age=abc");
+    /// assert_eq!(warnings[1].kind, ParseWarningKind::DuplicateAttribute);
+    /// assert_eq!(&input[warnings[1].span.clone()], "Path=/b");
+    /// ```
+    pub fn parse_with_warnings(s: &str, mode: ParseMode) -> Result<(SetCookie, Vec<ParseWarning>), ParseError> {
+        return SetCookie::parse_with_warnings_at(s, mode, SystemTime::now());
+    }
+
+    /// Parses a `Set-Cookie` header value received at the time `now` and gets the non-fatal issues, see
+    /// [SetCookie::parse_with_warnings]. `Max-Age` is counted from `now` and an `Expires` date is in the past
+    /// if it is before `now`.
+    ///
+    /// ```rust
+    /// use wcookie::{ParseMode, ParseWarningKind, SetCookie};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let input = "id=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT";
+    ///
+    /// let (_, warnings) = SetCookie::parse_with_warnings_at(input, ParseMode::Lenient, UNIX_EPOCH).unwrap();
+    /// assert!(warnings.is_empty());
+    ///
+    /// let now = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    /// let (_, warnings) = SetCookie::parse_with_warnings_at(input, ParseMode::Lenient, now).unwrap();
+    /// assert_eq!(warnings[0].kind, ParseWarningKind::ExpiresInPast);
+    /// ```
+    pub fn parse_with_warnings_at(s: &str, mode: ParseMode, now: SystemTime) -> Result<(SetCookie, Vec<ParseWarning>), ParseError> {
+        let mut warnings = Warnings::new(now);
+        let mut cookie = match mode {
+            ParseMode::Strict => parse_strict(s, &mut warnings)?,
+            ParseMode::Lenient => parse_lenient(s, &mut warnings)?
+        };
+        if !cookie.satisfies_prefix() {
+            let name_value = s.split(';').next().unwrap_or(s);
+            warnings.push(ParseWarningKind::InvalidPrefix, &cookie.name, trimmed_span(name_value, 0));
+        }
+        cookie.created = now;
+        return Ok((cookie, warnings.list));
+    }
+}
+
 impl FromStr for SetCookie {
     type Err = ParseError;
 
+    /// Parses a `Set-Cookie` header value at [ParseMode::Strict] mode
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut components = s.split(';');
-     
-            return if let Some(slice) = components.next() {
-                let (key, value) = parse_cookie_value(slice)?;
-                let mut cookie = SetCookie::new(key, value);
-                while let Some(param) = components.next() {
-                    let directive = CookieDirective::from_str(param)?;
-                    match directive {
-                        CookieDirective::Expires(date) =>
-                           cookie.expires = Some(date),
-                        CookieDirective::MaxAge(seconds) => 
-                           cookie.max_age = Some(seconds),
-                        CookieDirective::Domain(url) =>  // starting dot is ignored                      
-                           cookie.domain = Some(if let Some(stripped) = url.as_str().strip_prefix(".") {
-                               String::from(stripped)
-                           } else {
-                               url
-                           }),
-                        CookieDirective::Path(path) => cookie.path = Some(path),
-                        CookieDirective::SameSite(val) => cookie.same_site = val,
-                        CookieDirective::Secure => cookie.secure = true,
-                        CookieDirective::HttpOnly => cookie.http_only = true,
-                        CookieDirective::Extension(name, value) => {
-                            let _res = cookie.extensions.insert(name, value);
-                        }
+        return SetCookie::parse(s, ParseMode::Strict);
+    }
+}
+
+/// Helper to collect [ParseWarning] while parsing attributes received at the time `now`
+struct Warnings {
+    now: SystemTime,
+    seen: HashSet<String>,
+    list: Vec<ParseWarning>
+}
+
+impl Warnings {
+    fn new(now: SystemTime) -> Warnings {
+        Warnings { now, seen: HashSet::new(), list: Vec::new() }
+    }
+
+    fn push(&mut self, kind: ParseWarningKind, attribute: &str, span: Range<usize>) {
+        self.list.push(ParseWarning { kind, attribute: String::from(attribute), span });
+    }
+
+    /// Registers the attribute `name`, in lowercase, and warns if it was already found
+    fn attribute(&mut self, name: &str, span: Range<usize>) {
+        if !self.seen.insert(String::from(name)) {
+            self.push(ParseWarningKind::DuplicateAttribute, name, span);
+        }
+    }
+
+    /// Warns if `date` is before `now`
+    fn expires(&mut self, date: &DateTime<Utc>, span: Range<usize>) {
+        if *date < DateTime::<Utc>::from(self.now) {
+            self.push(ParseWarningKind::ExpiresInPast, COOKIE_EXPIRES, span);
+        }
+    }
+}
+
+/// Splits a `Set-Cookie` value by `;`, with the byte offset where each part starts
+fn split_attributes(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut start = 0;
+    s.split(';').map(move |part| {
+        let item = (start, part);
+        start += part.len() + 1;
+        item
+    })
+}
+
+/// Byte offsets of `part` without leading and trailing white spaces, `part` starts at `start`
+fn trimmed_span(part: &str, start: usize) -> Range<usize> {
+    let leading = part.len() - part.trim_start().len();
+    let begin = start + leading;
+    return begin..begin + part.trim().len();
+}
+
+/// Parses a `Set-Cookie` value, failing on any invalid attribute
+fn parse_strict(s: &str, warnings: &mut Warnings) -> Result<SetCookie, ParseError> {
+    let mut components = split_attributes(s);
+
+    // Split returns at least one item
+    let (_, slice) = components.next().unwrap_or((0, s));
+    let (key, value) = parse_cookie_value(slice)?;
+    let mut cookie = SetCookie::new(key, value);
+
+    for (start, param) in components {
+        let directive = CookieDirective::from_str(param).map_err(|e| e.offset(start))?;
+        let span = trimmed_span(param, start);
+        match directive {
+            CookieDirective::Expires(date) => {
+                warnings.attribute(COOKIE_EXPIRES, span.clone());
+                warnings.expires(&date, span);
+                cookie.expires = Some(date)
+            },
+            CookieDirective::MaxAge(seconds) => {
+                warnings.attribute(COOKIE_MAX_AGE, span);
+                cookie.max_age = Some(seconds)
+            },
+            CookieDirective::Domain(url) => { // starting dot is ignored
+                warnings.attribute(COOKIE_DOMAIN, span);
+                cookie.domain = Some(if let Some(stripped) = url.as_str().strip_prefix(".") {
+                    String::from(stripped)
+                } else {
+                    url
+                })
+            },
+            CookieDirective::Path(path) => {
+                warnings.attribute(COOKIE_PATH, span);
+                cookie.path = Some(path)
+            },
+            CookieDirective::SameSite(val) => {
+                warnings.attribute(COOKIE_SAME_SITE, span);
+                cookie.same_site = val
+            },
+            CookieDirective::Secure => {
+                warnings.attribute(COOKIE_SECURE, span);
+                cookie.secure = true
+            },
+            CookieDirective::HttpOnly => {
+                warnings.attribute(COOKIE_HTTP_ONLY, span);
+                cookie.http_only = true
+            },
+            CookieDirective::Partitioned => {
+                warnings.attribute(COOKIE_PARTITIONED, span);
+                cookie.partitioned = true
+            },
+            CookieDirective::Priority(priority) => {
+                warnings.attribute(COOKIE_PRIORITY, span);
+                cookie.priority = priority
+            },
+            CookieDirective::Extension(name, value) => {
+                warnings.attribute(&name, span.clone());
+                warnings.push(ParseWarningKind::UnknownAttribute, &name, span);
+                let _res = cookie.extensions.insert(name, value);
+            }
+        }
+    }         
+    return Ok(cookie);
+}
+
+/// Parses a `Set-Cookie` value with [RFC6265 Section 5.2](https://datatracker.ietf.org/doc/html/rfc6265#section-5.2) algorithm
+fn parse_lenient(s: &str, warnings: &mut Warnings) -> Result<SetCookie, ParseError> {
+    let mut components = split_attributes(s);
+
+    // Split returns at least one item
+    let (_, name_value) = components.next().unwrap_or((0, s));
+
+    let index = name_value.find('=')
+        .ok_or_else(|| ParseError::new(ParseErrorKind::Malformed, format!("Malformed HTTP cookie: {}", name_value))
+            .with_span(trimmed_span(name_value, 0)))?;
+    let name = name_value[0..index].trim();
+    let value = name_value[index + 1..].trim();
+
+    if name.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::MissingName, "Cookie has not got name")
+            .with_span(trimmed_span(name_value, 0)));
+    }
+
+    let mut cookie = SetCookie::new(name, value);
+
+    for (start, attribute) in components {
+        let (key, value) = match attribute.find('=') {
+            Some(index) => (attribute[0..index].trim(), attribute[index + 1..].trim()),
+            None => (attribute.trim(), "")
+        };
+
+        let span = trimmed_span(attribute, start);
+        let key = key.to_ascii_lowercase();
+        if !key.is_empty() {
+            warnings.attribute(&key, span.clone());
+        }
+
+        match key.as_str() {
+            COOKIE_EXPIRES => {
+                if let Ok(date) = parse_cookie_date(value) {
+                    warnings.expires(&date, span);
+                    cookie.expires = Some(date);
+                } else {
+                    warnings.push(ParseWarningKind::IgnoredAttribute, &key, span);
+                }
+            },
+            COOKIE_MAX_AGE => {
+                if let Some(duration) = parse_max_age(value) {
+                    cookie.max_age = Some(duration);
+                } else {
+                    warnings.push(ParseWarningKind::IgnoredAttribute, &key, span);
+                }
+            },
+            COOKIE_DOMAIN => {
+                if !value.is_empty() {
+                    cookie.domain = Some(value.strip_prefix('.').unwrap_or(value).to_ascii_lowercase());
+                } else {
+                    warnings.push(ParseWarningKind::IgnoredAttribute, &key, span);
+                }
+            },
+            COOKIE_PATH => {
+                if value.starts_with('/') {
+                    cookie.path = Some(String::from(value));
+                } else {
+                    cookie.path = None;
+                    warnings.push(ParseWarningKind::IgnoredAttribute, &key, span);
+                }
+            },
+            COOKIE_SAME_SITE => {
+                cookie.same_site = match SameSiteValue::from_str(value.to_ascii_lowercase().as_str()) {
+                    Ok(same_site) => same_site,
+                    Err(_) => {
+                        warnings.push(ParseWarningKind::IgnoredAttribute, &key, span);
+                        SameSiteValue::Unspecified
                     }
-                }         
-                Ok(cookie)
-            } else {
-                if CookieDirective::from_str(s).is_ok() {
-                    return Err(ParseError::new("Cookie has not got name/value"));
                 };
-    
-                let (key, value) = parse_cookie_value(s)?;            
-                Ok(SetCookie::new(key, value))
+            },
+            COOKIE_SECURE => cookie.secure = true,
+            COOKIE_HTTP_ONLY => cookie.http_only = true,
+            COOKIE_PARTITIONED => cookie.partitioned = true,
+            COOKIE_PRIORITY => {
+                cookie.priority = match CookiePriority::from_str(value) {
+                    Ok(priority) => priority,
+                    Err(_) => {
+                        warnings.push(ParseWarningKind::IgnoredAttribute, &key, span);
+                        CookiePriority::Medium
+                    }
+                };
+            },
+            "" => {},
+            other => {
+                warnings.push(ParseWarningKind::UnknownAttribute, other, span);
+                let value = if attribute.contains('=') { Some(String::from(value)) } else { None };
+                cookie.extensions.insert(String::from(other), value);
             }
         }
+    }
+
+    return Ok(cookie);
 }
 
 impl Hash for SetCookie {
@@ -515,64 +1276,239 @@ impl Hash for SetCookie {
 
 
 
-const MONTH_NAME: [&'static str; 12] = ["Jan" , "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+/// Options to write a `Set-Cookie` header value, see [SetCookie::serialize]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Write `Expires` along with `Max-Age`, for legacy clients that do not support `Max-Age`.
+    /// `Expires` is set to the cookie [expire time](SetCookie::expire_time).
+    pub legacy_expires: bool
+}
 
-impl fmt::Display for SetCookie {
-    
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> { 
+impl SetCookie {
+    /// Writes the cookie as a `Set-Cookie` header value with `options`.
+    /// 
+    /// ```rust
+    /// use wcookie::{SerializeOptions, SetCookie};
+    /// use std::time::Duration;
+    /// 
+    /// let mut cookie = SetCookie::new("id", "a3fWa");
+    /// cookie.max_age = Some(Duration::from_secs(3600));
+    /// 
+    /// let options = SerializeOptions { legacy_expires: true };
+    /// let value = cookie.serialize(options);
+    /// 
+    /// assert!(value.starts_with("id=a3fWa; Max-Age=3600; Expires="));
+    /// assert!(value.ends_with(" GMT"));
+    /// ```
+    pub fn serialize(&self, options: SerializeOptions) -> String {
+        let mut result = String::new();
+        // Writing to a String does not fail
+        let _ = self.write_to(&mut result, options);
+        return result;
+    }
+
+    /// Checks the cookie can be safely written in a `Set-Cookie` header, following
+    /// [RFC6265 Section 4.1.1](https://datatracker.ietf.org/doc/html/rfc6265#section-4.1.1) grammar:
+    /// 
+    /// * The name is a token: no controls, white spaces nor separators such as `=` or `;`.
+    /// * The value has only `cookie-octet` characters, optionally enclosed in double quotes.
+    /// * The domain is a host name.
+    /// * The path and extensions have not got control characters nor `;`. Extension names are tokens.
+    /// * Extensions are not named after an attribute with its own field, such as `Priority` or `SameSite`.
+    /// * The attributes meet the requirements of the name prefix, if any, see [SetCookie::satisfies_prefix].
+    /// * `Partitioned` cookies are `Secure`.
+    /// 
+    /// ```rust
+    /// use wcookie::{SetCookie, ValidationError};
+    /// 
+    /// let cookie = SetCookie::new("a", "x\r\nSet-Cookie: admin=1");
+    /// 
+    /// assert_eq!(cookie.validate(), Err(ValidationError::InvalidValue(String::from("x\r\nSet-Cookie: admin=1"))));
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !is_token(&self.name) {
+            return Err(ValidationError::InvalidName(self.name.clone()));
+        }
+
+        if !is_cookie_value(&self.value) {
+            return Err(ValidationError::InvalidValue(self.value.clone()));
+        }
+
+        if let Some(ref domain) = self.domain {
+            if !self.host_only && !is_domain(domain) {
+                return Err(ValidationError::InvalidDomain(domain.clone()));
+            }
+        }
+
+        if let Some(ref path) = self.path {
+            if !is_attribute_value(path) {
+                return Err(ValidationError::InvalidPath(path.clone()));
+            }
+        }
+
+        for (key, value) in &self.extensions {
+            if !is_token(key) || !is_attribute_value(value.as_deref().unwrap_or_default()) {
+                return Err(ValidationError::InvalidExtension(key.clone()));
+            }
+            if is_reserved_attribute(key) {
+                return Err(ValidationError::ReservedExtension(key.clone()));
+            }
+        }
+
+        if !self.satisfies_prefix() {
+            return Err(ValidationError::InvalidPrefix(self.name.clone()));
+        }
+
+        if self.partitioned && !self.secure {
+            return Err(ValidationError::PartitionedNotSecure(self.name.clone()));
+        }
+
+        return Ok(());
+    }
+
+    /// Writes the cookie as a `Set-Cookie` header value, after checking it with [SetCookie::validate].
+    /// 
+    /// Use this function instead of `to_string` when the name, value or attributes come from untrusted input,
+    /// to avoid header injection.
+    /// 
+    /// ```rust
+    /// use wcookie::SetCookie;
+    /// 
+    /// assert_eq!(SetCookie::new("id", "a3fWa").try_to_string().unwrap(), "id=a3fWa");
+    /// assert!(SetCookie::new("id", "a3fWa; Domain=evil.org").try_to_string().is_err());
+    /// ```
+    pub fn try_to_string(&self) -> Result<String, ValidationError> {
+        return self.try_serialize(SerializeOptions::default());
+    }
+
+    /// Writes the cookie as a `Set-Cookie` header value with `options`, after checking it with [SetCookie::validate].
+    pub fn try_serialize(&self, options: SerializeOptions) -> Result<String, ValidationError> {
+        self.validate()?;
+        return Ok(self.serialize(options));
+    }
+
+    fn write_to<W: fmt::Write>(&self, f: &mut W, options: SerializeOptions) -> fmt::Result {
         write!(f, "{}={}", self.name, self.value)?;
         if let Some(ref domain) =  self.domain {
-            write!(f, ", Domain={}", domain)?;
+            if !self.host_only {
+                write!(f, "; Domain={}", domain)?;
+            }
         }
         if let Some(ref path) =  self.path {
-            write!(f, ", Path={}", path)?;
+            write!(f, "; Path={}", path)?;
         }
 
         if let Some(duration) = self.max_age {
-            write!(f, ", Max-Age={}", duration.as_secs())?;
-        }else if let Some(ref date) = self.expires {
-            write!(f, ", Expires={}, {:02}-{}-{} {:02}:{:02}:{:02} GMT",
-                   date.weekday(), date.day(), MONTH_NAME[(date.month()-1) as usize], date.year(),
-                   date.hour(), date.minute(), date.second())?;
+            write!(f, "; Max-Age={}", duration.as_secs())?;
+            if options.legacy_expires {
+                if let Some(time) = self.expire_time() {
+                    write!(f, "; Expires={}", HttpDate::from(time))?;
+                }
+            }
+        } else if let Some(ref date) = self.expires {
+            write!(f, "; Expires={}", HttpDate::from(*date))?;
         } 
+
         match self.same_site {
-            SameSiteValue::None => write!(f, ", SameSite=None")?,
-            SameSiteValue::Strict => write!(f, ", SameSite=Strict")?,
-            _ => {}
+            SameSiteValue::None => write!(f, "; SameSite=None")?,
+            SameSiteValue::Strict => write!(f, "; SameSite=Strict")?,
+            SameSiteValue::Lax => write!(f, "; SameSite=Lax")?,
+            SameSiteValue::Unspecified => {}
         };
 
         if self.secure {
-            write!(f, ", Secure")?;
+            write!(f, "; Secure")?;
         }
 
         if self.http_only {
-            write!(f, ", HttpOnly")?;
+            write!(f, "; HttpOnly")?;
         }
 
-        for (key, value) in &self.extensions {
+        if self.partitioned {
+            write!(f, "; Partitioned")?;
+        }
+
+        if self.priority != CookiePriority::Medium {
+            write!(f, "; Priority={}", self.priority)?;
+        }
+
+        // Extensions named after known attributes would duplicate the typed fields written above
+        for (key, value) in self.extensions.iter().filter(|(key, _)| !is_reserved_attribute(key)) {
             if let Some(val) = value {
-                write!(f, ", {}={}", key, val)?;
+                write!(f, "; {}={}", key, val)?;
             } else {
-                write!(f, ", {}", key)?;
+                write!(f, "; {}", key)?;
             }
         }
 
         return Ok(());
-        
     }
 }
 
+impl fmt::Display for SetCookie {
+    /// Writes the cookie as a `Set-Cookie` header value, with attributes separated by `"; "`
+    /// and `Expires` as an IMF-fixdate. See [SetCookie::serialize].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> { 
+        self.write_to(f, SerializeOptions::default())
+    }
+}
+
+/// Gets the default cookie path for a request path, using
+/// [RFC6265 Section 5.1.4](https://datatracker.ietf.org/doc/html/rfc6265#section-5.1.4) algorithm:
+/// the request path up to, but not including, its right-most `/`. If the path does not start with `/`
+/// or it has only one `/`, the default path is `/`.
+/// 
+/// ```rust
+/// use wcookie::default_path;
+/// 
+/// assert_eq!(default_path("/app/login"), "/app");
+/// assert_eq!(default_path("/app/"), "/app");
+/// assert_eq!(default_path("/login"), "/");
+/// assert_eq!(default_path(""), "/");
+/// ```
+pub fn default_path(request_path: &str) -> &str {
+    if !request_path.starts_with('/') {
+        return "/";
+    }
+    return match request_path.rfind('/') {
+        Some(index) if index > 0 => &request_path[0..index],
+        _ => "/"
+    };
+}
+
+/// Parses a `Max-Age` value as a signed number of seconds, see
+/// [RFC6265 Section 5.2.2](https://datatracker.ietf.org/doc/html/rfc6265#section-5.2.2).
+/// 
+/// Zero or negative values are a zero duration, so the cookie expires at once.
+/// Values too large for a `u64` are the maximum duration.
+fn parse_max_age(value: &str) -> Option<Duration> {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if value.starts_with('-') {
+        return Some(Duration::ZERO);
+    }
+    return Some(Duration::from_secs(u64::from_str(digits).unwrap_or(u64::MAX)));
+}
+
 /// Helper function to parse the `Cookie` name and value
 pub(crate) fn parse_cookie_value(cookie: &str) -> Result<(String, String), ParseError>{
     if let Some(index) = cookie.find('=') {
         let key = String::from(cookie[0..index].trim());
         let value = String::from(cookie[index + 1..].trim());
+        if key.is_empty() {
+            return Err(ParseError::new(ParseErrorKind::MissingName, "Cookie name must not be empty")
+                .with_span(trimmed_span(cookie, 0)));
+        }
         if value.len() == 0 {
-            return Err(ParseError::new("Cookie value must not be empty"));
+            return Err(ParseError::new(ParseErrorKind::EmptyValue, "Cookie value must not be empty")
+                .with_span(index + 1..cookie.len()));
         }
         return Ok((key, value));
     } else {
-        return Err(ParseError::new(format!("Malformed HTTP cookie: {}", cookie)));
+        return Err(ParseError::new(ParseErrorKind::Malformed, format!("Malformed HTTP cookie: {}", cookie))
+            .with_span(trimmed_span(cookie, 0)));
     }
 }
 
@@ -585,6 +1521,8 @@ enum CookieDirective {
     SameSite(SameSiteValue),
     Secure,
     HttpOnly,
+    Partitioned,
+    Priority(CookiePriority),
     Extension(String, Option<String>)
 }
 
@@ -600,23 +1538,22 @@ impl FromStr for CookieDirective {
         if let Some(index) = s.find('=') { // Cookie param with value
             let key = s[0..index].trim().to_ascii_lowercase();
             let value = s[index + 1..].trim();
+            let value_span = trimmed_span(&s[index + 1..], index + 1);
             if value.len() == 0 {
-                return Err(ParseError::new(format!("Directive {} value must not be empty", key)));
+                return Err(ParseError::new(ParseErrorKind::DirectiveNeedsValue, format!("Directive {} value must not be empty", key))
+                    .with_span(trimmed_span(s, 0)));
             }
             return match key.as_str() {
                 COOKIE_EXPIRES => {
-                    let expires = parse_rfc_1123_date(value)
-                        .or_else(|_| parse_rfc_850_date(value))
-                        .or_else(|_| parse_asct_date(value))?; 
+                    let expires = HttpDate::from_str(value)
+                        .map_err(|e| e.with_span(value_span))?; 
 
-                    Ok(CookieDirective::Expires(expires))
+                    Ok(CookieDirective::Expires(DateTime::from(expires)))
                 },
                 COOKIE_MAX_AGE => {  // Max-age value in seconds
-                    let digit = u64::from_str(value)
-                        .or_else(|_|  {
-                            Err(ParseError::new("Cannot parse Max-age"))
-                        })?;
-                    Ok(CookieDirective::MaxAge(Duration::from_secs(digit)))
+                    let duration = parse_max_age(value)
+                        .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidMaxAge, "Cannot parse Max-age").with_span(value_span))?;
+                    Ok(CookieDirective::MaxAge(duration))
                 },
                 COOKIE_DOMAIN => {
                     Ok(CookieDirective::Domain(String::from(value)))
@@ -624,11 +1561,15 @@ impl FromStr for CookieDirective {
                 COOKIE_PATH => {
                     Ok(CookieDirective::Path(String::from(value)))
                 }
+                COOKIE_PRIORITY => {
+                    let priority = CookiePriority::from_str(value).map_err(|e| e.with_span(value_span))?;
+                    Ok(CookieDirective::Priority(priority))
+                },
                 COOKIE_SAME_SITE => {
                     let lower_case = value.to_ascii_lowercase();
                     match SameSiteValue::from_str(lower_case.as_str()) {
                         Ok(site_value) => Ok(CookieDirective::SameSite(site_value)),
-                        Err(e) => Err(e)
+                        Err(e) => Err(e.with_span(value_span))
                     }
                 },
                 _ => Ok(CookieDirective::Extension(key, Some(value.to_string())))
@@ -639,7 +1580,10 @@ impl FromStr for CookieDirective {
             match directive.as_str() {
                 COOKIE_SECURE => Ok(CookieDirective::Secure),
                 COOKIE_HTTP_ONLY => Ok(CookieDirective::HttpOnly),
-                COOKIE_DOMAIN | COOKIE_EXPIRES | COOKIE_MAX_AGE | COOKIE_PATH |COOKIE_SAME_SITE => Err(ParseError::new(format!("Directive {} needs a value", directive))),
+                COOKIE_PARTITIONED => Ok(CookieDirective::Partitioned),
+                COOKIE_DOMAIN | COOKIE_EXPIRES | COOKIE_MAX_AGE | COOKIE_PATH | COOKIE_SAME_SITE | COOKIE_PRIORITY => 
+                    Err(ParseError::new(ParseErrorKind::DirectiveNeedsValue, format!("Directive {} needs a value", directive))
+                        .with_span(trimmed_span(s, 0))),
                 _ => Ok(CookieDirective::Extension(directive, None))
             }
         }
@@ -0,0 +1,100 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::StoredCookie;
+
+/// Default maximum size of a cookie, in bytes, see [StoredCookie::size]
+pub const DEFAULT_MAX_COOKIE_SIZE: usize = 4096;
+
+/// Default maximum number of cookies for each domain, as used by browsers
+pub const DEFAULT_MAX_COOKIES_PER_DOMAIN: usize = 180;
+
+/// Default maximum number of cookies at a jar
+pub const DEFAULT_MAX_COOKIES: usize = 3000;
+
+/// Storage limits of a [CookieJar](crate::CookieJar), see [CookieJar::set_limits](crate::CookieJar::set_limits).
+///
+/// [RFC6265 Section 6.1](https://datatracker.ietf.org/doc/html/rfc6265#section-6.1) requires user agents to
+/// store at least 4096 bytes per cookie, 50 cookies per domain and 3000 cookies in total. `None` disables a limit.
+///
+/// ```rust
+/// use wcookie::JarLimits;
+///
+/// let limits = JarLimits {
+///     max_cookies_per_domain: Some(50),
+///     max_total_size: Some(1024 * 1024),
+///     ..JarLimits::default()
+/// };
+///
+/// assert_eq!(limits.max_cookie_size, Some(4096));
+/// assert_eq!(limits.max_cookies, Some(3000));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct JarLimits {
+    /// Maximum size of a cookie, in bytes. Larger cookies are not stored.
+    pub max_cookie_size: Option<usize>,
+    /// Maximum number of cookies for each domain. Cookies of a domain and its subdomains count together.
+    pub max_cookies_per_domain: Option<usize>,
+    /// Maximum number of cookies at the jar
+    pub max_cookies: Option<usize>,
+    /// Maximum size of all the cookies at the jar, in bytes
+    pub max_total_size: Option<usize>
+}
+
+impl JarLimits {
+    /// Limits that are never reached
+    pub fn unlimited() -> JarLimits {
+        JarLimits {
+            max_cookie_size: None,
+            max_cookies_per_domain: None,
+            max_cookies: None,
+            max_total_size: None
+        }
+    }
+}
+
+impl Default for JarLimits {
+    /// [DEFAULT_MAX_COOKIE_SIZE], [DEFAULT_MAX_COOKIES_PER_DOMAIN] and [DEFAULT_MAX_COOKIES], without total size limit
+    fn default() -> Self {
+        JarLimits {
+            max_cookie_size: Some(DEFAULT_MAX_COOKIE_SIZE),
+            max_cookies_per_domain: Some(DEFAULT_MAX_COOKIES_PER_DOMAIN),
+            max_cookies: Some(DEFAULT_MAX_COOKIES),
+            max_total_size: None
+        }
+    }
+}
+
+/// Why a cookie was evicted from a [CookieJar](crate::CookieJar)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EvictionReason {
+    /// The cookie was expired, it was removed while enforcing a limit
+    Expired,
+    /// The domain of the cookie had too many cookies
+    DomainLimit,
+    /// The jar had too many cookies
+    TotalLimit,
+    /// The cookies of the jar were too large
+    TotalSizeLimit
+}
+
+/// Cookie removed from a [CookieJar](crate::CookieJar) to meet its [JarLimits],
+/// see [CookieJar::take_evictions](crate::CookieJar::take_evictions)
+#[derive(Debug, Clone)]
+pub struct EvictionEvent {
+    /// Removed cookie
+    pub cookie: StoredCookie,
+    /// Why it was removed
+    pub reason: EvictionReason
+}
@@ -0,0 +1,70 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+use crate::SameSiteValue;
+
+/// Maximum age of cookies without `SameSite` sent at cross-site top-level navigations with unsafe methods,
+/// 2 minutes, as used by browsers. See [CookiePolicy::lax_allowing_unsafe].
+pub const LAX_ALLOWING_UNSAFE_MAX_AGE: Duration = Duration::from_secs(2 * 60);
+
+/// Browser behaviors used to decide which cookies are sent in a request, see
+/// [SetCookie::use_in_context_with_policy](crate::SetCookie::use_in_context_with_policy) and
+/// [CookieJar::set_policy](crate::CookieJar::set_policy).
+///
+/// By default, cookies without `SameSite` are handled as `Lax` and the other behaviors are disabled.
+///
+/// ```rust
+/// use wcookie::{CookiePolicy, RequestContext, RequestUrl, SetCookie};
+/// use std::str::FromStr;
+///
+/// let policy = CookiePolicy { schemeful_same_site: true, ..CookiePolicy::default() };
+/// let cookie = SetCookie::from_response("id=1; SameSite=Strict", "https://example.com/").unwrap();
+///
+/// // Link from the http version of the site
+/// let mut context = RequestContext::new("https://example.com/").unwrap();
+/// context.initiator = Some(RequestUrl::from_str("http://example.com/").unwrap());
+///
+/// assert!(cookie.use_in_context(&context));
+/// assert!(!cookie.use_in_context_with_policy(&context, &policy));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CookiePolicy {
+    /// Schemeful same-site: requests are only same-site if they have the same scheme than the top-level site and
+    /// the initiator, see [RequestContext::is_schemeful_same_site](crate::RequestContext::is_schemeful_same_site).
+    /// So `http://example.com` and `https://example.com` are cross-site.
+    pub schemeful_same_site: bool,
+    /// Origin-bound cookies: stored cookies are only sent to the scheme and port of the request that set them,
+    /// see [StoredCookie::source_scheme](crate::StoredCookie::source_scheme). Cookies with a `Domain` are still
+    /// sent to subdomains.
+    pub origin_bound: bool,
+    /// How cookies without `SameSite`, [SameSiteValue::Unspecified], are handled: `Lax` by default, as current
+    /// browsers do, or `None` for legacy browsers. `Unspecified` is handled as `Lax`.
+    pub default_same_site: SameSiteValue,
+    /// Lax-allowing-unsafe: cookies without `SameSite` handled as `Lax` are also sent at cross-site top-level
+    /// navigations with unsafe methods, such as `POST`, if they were created at most this time ago.
+    /// Disabled by default, browsers use [LAX_ALLOWING_UNSAFE_MAX_AGE].
+    pub lax_allowing_unsafe: Option<Duration>
+}
+
+impl Default for CookiePolicy {
+    fn default() -> Self {
+        CookiePolicy {
+            schemeful_same_site: false,
+            origin_bound: false,
+            default_same_site: SameSiteValue::Lax,
+            lax_allowing_unsafe: None
+        }
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+const SECURE_PREFIX: &str = "__Secure-";
+const HOST_PREFIX: &str = "__Host-";
+
+/// Cookie name prefix, as defined in
+/// [RFC6265bis Section 4.1.3](https://datatracker.ietf.org/doc/html/draft-ietf-httpbis-rfc6265bis#section-4.1.3).
+///
+/// Prefixes are matched case-insensitively, so `__SECURE-id` has the [CookiePrefix::Secure] prefix.
+///
+/// ```rust
+/// use wcookie::CookiePrefix;
+///
+/// assert_eq!(CookiePrefix::of("__Host-id"), Some(CookiePrefix::Host));
+/// assert_eq!(CookiePrefix::of("__secure-id"), Some(CookiePrefix::Secure));
+/// assert_eq!(CookiePrefix::of("id"), None);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CookiePrefix {
+    /// `__Secure-`: the cookie must have the `Secure` attribute
+    Secure,
+    /// `__Host-`: the cookie must have the `Secure` attribute, must not have a `Domain` and its `Path` must be `/`
+    Host
+}
+
+impl CookiePrefix {
+    /// Gets the prefix of the cookie `name`, if any
+    pub fn of(name: &str) -> Option<CookiePrefix> {
+        if starts_with_ignore_case(name, SECURE_PREFIX) {
+            return Some(CookiePrefix::Secure);
+        }
+        if starts_with_ignore_case(name, HOST_PREFIX) {
+            return Some(CookiePrefix::Host);
+        }
+        return None;
+    }
+
+    /// Gets the prefix text
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CookiePrefix::Secure => SECURE_PREFIX,
+            CookiePrefix::Host => HOST_PREFIX
+        }
+    }
+}
+
+impl fmt::Display for CookiePrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Checks if `name` starts with `prefix`, ignoring ASCII case
+fn starts_with_ignore_case(name: &str, prefix: &str) -> bool {
+    name.as_bytes().get(0..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CookiePrefix;
+
+    #[test]
+    fn test_prefix() {
+        assert_eq!(CookiePrefix::of("__Secure-"), Some(CookiePrefix::Secure));
+        assert_eq!(CookiePrefix::of("__SECURE-id"), Some(CookiePrefix::Secure));
+        assert_eq!(CookiePrefix::of("__host-id"), Some(CookiePrefix::Host));
+        assert_eq!(CookiePrefix::of("__Host"), None);
+        assert_eq!(CookiePrefix::of("_Host-id"), None);
+        assert_eq!(CookiePrefix::of("Host-id"), None);
+        assert_eq!(CookiePrefix::of("__Hóst-id"), None);
+        assert_eq!(CookiePrefix::Host.to_string(), "__Host-");
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use crate::is_ip_address;
+
+/// Copy of <https://publicsuffix.org/list/public_suffix_list.dat> bundled with the crate.
+/// The list is licensed under the [Mozilla Public License, v. 2.0](https://mozilla.org/MPL/2.0/).
+const BUNDLED_LIST: &str = include_str!("public_suffix_list.dat");
+
+lazy_static! {
+    static ref BUNDLED: Arc<PublicSuffixList> = Arc::new(PublicSuffixList::parse(BUNDLED_LIST));
+}
+
+/// Rules of a [Public Suffix List](https://publicsuffix.org/), used to reject "supercookies" whose `Domain`
+/// is a public suffix such as `com` or `co.uk`, as required by
+/// [RFC6265 Section 5.3](https://datatracker.ietf.org/doc/html/rfc6265#section-5.3) step 5.
+///
+/// The crate bundles a copy of the list, licensed under MPL-2.0, see
+/// [PublicSuffixList::bundled]. A newer list can be loaded
+/// with [PublicSuffixList::from_file] and set at a [CookieJar](crate::CookieJar).
+///
+/// ```rust
+/// use wcookie::PublicSuffixList;
+///
+/// let list = PublicSuffixList::parse("com\nuk\nco.uk\n*.ck\n!www.ck");
+///
+/// assert!(list.is_public_suffix("co.uk"));
+/// assert_eq!(list.public_suffix("www.example.co.uk"), "co.uk");
+/// assert_eq!(list.registrable_domain("www.example.co.uk"), Some("example.co.uk"));
+/// assert_eq!(list.registrable_domain("a.b.ck"), Some("a.b.ck"));
+/// assert_eq!(list.registrable_domain("www.ck"), Some("www.ck"));
+/// ```
+///
+/// Rules are matched case-insensitively as written at the list. Internationalized rules only match
+/// host names in Unicode form.
+#[derive(Clone, Default)]
+pub struct PublicSuffixList {
+    /// Normal rules, such as `co.uk`
+    rules: HashSet<String>,
+    /// Wildcard rules without the leading `*.`, such as `ck` for `*.ck`
+    wildcards: HashSet<String>,
+    /// Exception rules without the leading `!`, such as `www.ck` for `!www.ck`
+    exceptions: HashSet<String>
+}
+
+impl PublicSuffixList {
+    /// Parses the content of a `public_suffix_list.dat` file.
+    ///
+    /// Each line has a rule, only the text until the first white space is used.
+    /// Empty lines and lines starting with `//` are ignored.
+    pub fn parse(content: &str) -> PublicSuffixList {
+        let mut list = PublicSuffixList::default();
+
+        for line in content.lines() {
+            let rule = match line.split_whitespace().next() {
+                Some(rule) if !rule.starts_with("//") => rule.to_lowercase(),
+                _ => continue
+            };
+
+            if let Some(exception) = rule.strip_prefix('!') {
+                list.exceptions.insert(String::from(exception));
+            } else if let Some(wildcard) = rule.strip_prefix("*.") {
+                list.wildcards.insert(String::from(wildcard));
+            } else {
+                list.rules.insert(rule);
+            }
+        }
+
+        return list;
+    }
+
+    /// Loads a `public_suffix_list.dat` file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<PublicSuffixList> {
+        let content = fs::read_to_string(path)?;
+        return Ok(PublicSuffixList::parse(&content));
+    }
+
+    /// Gets the list bundled with the crate
+    pub fn bundled() -> Arc<PublicSuffixList> {
+        BUNDLED.clone()
+    }
+
+    /// Number of rules at the list
+    pub fn len(&self) -> usize {
+        self.rules.len() + self.wildcards.len() + self.exceptions.len()
+    }
+
+    /// Checks if the list has not got rules
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the public suffix of `host`. If no rule matches, the public suffix is the last label
+    /// (the implicit `*` rule). A trailing dot is ignored.
+    pub fn public_suffix<'a>(&self, host: &'a str) -> &'a str {
+        let host = host.strip_suffix('.').unwrap_or(host);
+        let lowercase = host.to_lowercase();
+
+        // Byte offsets where each label starts, for the original and the lowercase host.
+        // Lowercase may change the length of non ASCII characters, so both are computed.
+        let starts = label_starts(host);
+        let lower_starts = label_starts(&lowercase);
+
+        for (index, start) in lower_starts.iter().enumerate() {
+            let candidate = &lowercase[*start..];
+
+            if self.exceptions.contains(candidate) {
+                // An exception rule public suffix is the rule without its leftmost label
+                let next = starts.get(index + 1).copied().unwrap_or(host.len());
+                return &host[next..];
+            }
+
+            if self.rules.contains(candidate) {
+                return &host[starts[index]..];
+            }
+
+            if let Some(next) = lower_starts.get(index + 1) {
+                if self.wildcards.contains(&lowercase[*next..]) {
+                    return &host[starts[index]..];
+                }
+            }
+        }
+
+        return &host[*starts.last().unwrap()..];
+    }
+
+    /// Checks if `domain` is a public suffix. A leading dot is ignored.
+    pub fn is_public_suffix(&self, domain: &str) -> bool {
+        let domain = domain.strip_prefix('.').unwrap_or(domain);
+        let domain = domain.strip_suffix('.').unwrap_or(domain);
+        if domain.is_empty() || is_ip_address(domain) {
+            return false;
+        }
+        return self.public_suffix(domain).len() == domain.len();
+    }
+
+    /// Gets the registrable domain of `host`: its public suffix and the label before it,
+    /// for example `example.co.uk` for `www.example.co.uk`.
+    ///
+    /// Returns `None` if `host` is an IP address or a public suffix.
+    pub fn registrable_domain<'a>(&self, host: &'a str) -> Option<&'a str> {
+        let host = host.strip_suffix('.').unwrap_or(host);
+        if host.is_empty() || is_ip_address(host) {
+            return None;
+        }
+
+        let suffix = self.public_suffix(host);
+        if suffix.len() >= host.len() {
+            return None;
+        }
+
+        let prefix = &host[0..host.len() - suffix.len() - 1];
+        let start = prefix.rfind('.').map(|index| index + 1).unwrap_or(0);
+        return Some(&host[start..]);
+    }
+}
+
+impl fmt::Debug for PublicSuffixList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PublicSuffixList")
+            .field("rules", &self.rules.len())
+            .field("wildcards", &self.wildcards.len())
+            .field("exceptions", &self.exceptions.len())
+            .finish()
+    }
+}
+
+/// Byte offsets where the labels of `host` start, from the leftmost label
+fn label_starts(host: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(host.match_indices('.').map(|(index, _)| index + 1));
+    return starts;
+}
+
+/// Gets the registrable domain of `host` using the bundled Public Suffix List.
+/// See [PublicSuffixList::registrable_domain].
+///
+/// ```rust
+/// use wcookie::registrable_domain;
+///
+/// assert_eq!(registrable_domain("www.example.co.uk"), Some("example.co.uk"));
+/// assert_eq!(registrable_domain("co.uk"), None);
+/// ```
+pub fn registrable_domain(host: &str) -> Option<&str> {
+    BUNDLED.registrable_domain(host)
+}
+
+/// Checks if `domain` is a public suffix using the bundled Public Suffix List.
+/// See [PublicSuffixList::is_public_suffix].
+pub fn is_public_suffix(domain: &str) -> bool {
+    BUNDLED.is_public_suffix(domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{PublicSuffixList, is_public_suffix, registrable_domain};
+
+    #[test]
+    fn test_bundled() {
+        assert!(!PublicSuffixList::bundled().is_empty());
+
+        assert!(is_public_suffix("com"));
+        assert!(is_public_suffix("co.uk"));
+        assert!(is_public_suffix(".co.uk"));
+        assert!(is_public_suffix("CO.UK"));
+        assert!(is_public_suffix("github.io"));
+        assert!(!is_public_suffix("example.com"));
+        assert!(!is_public_suffix("127.0.0.1"));
+        assert!(!is_public_suffix(""));
+    }
+
+    #[test]
+    fn test_registrable_domain() {
+        assert_eq!(registrable_domain("example.com"), Some("example.com"));
+        assert_eq!(registrable_domain("www.example.com"), Some("example.com"));
+        assert_eq!(registrable_domain("a.b.Example.co.uk"), Some("Example.co.uk"));
+        assert_eq!(registrable_domain("www.example.com."), Some("example.com"));
+        assert_eq!(registrable_domain("user.github.io"), Some("user.github.io"));
+        assert_eq!(registrable_domain("com"), None);
+        assert_eq!(registrable_domain("192.168.0.1"), None);
+    }
+
+    #[test]
+    fn test_unknown_tld() {
+        let list = PublicSuffixList::parse("com");
+
+        assert!(list.is_public_suffix("unknowntld"));
+        assert_eq!(list.registrable_domain("www.example.unknowntld"), Some("example.unknowntld"));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let list = PublicSuffixList::parse("// comment\n\nck\n*.ck\n!www.ck  ignored text");
+
+        assert_eq!(list.len(), 3);
+        assert!(list.is_public_suffix("ck"));
+        assert!(list.is_public_suffix("anything.ck"));
+        assert!(!list.is_public_suffix("www.ck"));
+        assert_eq!(list.registrable_domain("www.ck"), Some("www.ck"));
+        assert_eq!(list.registrable_domain("a.www.ck"), Some("www.ck"));
+        assert_eq!(list.registrable_domain("a.b.ck"), Some("a.b.ck"));
+        assert_eq!(list.registrable_domain("b.ck"), None);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join("wcookie_public_suffix_test.dat");
+        std::fs::write(&path, "example\n").unwrap();
+
+        let list = PublicSuffixList::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(list.is_public_suffix("example"));
+        assert!(PublicSuffixList::from_file("/nonexistent/public_suffix_list.dat").is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::NaiveDate;
 use regex::Regex;
-use crate::ParseError;
+use crate::{ParseError, ParseErrorKind};
 use chrono::Utc;
 use chrono::DateTime;
 
@@ -12,6 +12,8 @@ const DATE_FORMAT_1123: &str= "(Mon|Tue|Wed|Thu|Fri|Sat|Sun), \
 /// Parses RFC 1123 dates, as defined in [RFC2616 Section 3.3.1](https://datatracker.ietf.org/doc/html/rfc2616#section-3.3.1).
 /// 
 /// For example,  `Sun, 06 Nov 1994 08:49:37 GMT` date.
+/// 
+/// This is a strict parser, see [parse_cookie_date](crate::parse_cookie_date) for the user agent algorithm.
 pub fn parse_rfc_1123_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(DATE_FORMAT_1123).unwrap();
@@ -34,7 +36,7 @@ pub fn parse_rfc_1123_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
             "Oct" => 10,
             "Nov" => 11,
             "Dec" => 12,
-            _ => return Err(ParseError::new("Invalid date"))
+            _ => return Err(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))
         };
 
         let year: i32 = captures.get(4).unwrap().as_str().parse().unwrap();
@@ -44,12 +46,12 @@ pub fn parse_rfc_1123_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
         let secs : u32 = captures.get(7).unwrap().as_str().parse().unwrap();
 
         let naive = NaiveDate::from_ymd_opt(year, month, day)
-            .ok_or(ParseError::new("Invalid date"))?
+            .ok_or(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))?
             .and_hms_opt(hour,min,secs)
-            .ok_or(ParseError::new("Invalid date"))?;
+            .ok_or(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))?;
         
         return Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
     } else {
-        return Err(ParseError::new("Invalid date"));
+        return Err(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"));
     }
 }
\ No newline at end of file
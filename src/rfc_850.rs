@@ -1,6 +1,6 @@
 use chrono::NaiveDate;
 use regex::Regex;
-use crate::ParseError;
+use crate::{ParseError, ParseErrorKind};
 use chrono::Utc;
 use chrono::DateTime;
 
@@ -13,6 +13,8 @@ const DATE_FORMAT_850: &str= "(Monday|Tuesday|Wednesday|Thursday|Friday|Saturday
 /// 
 /// For example,  `Wed, 15-Nov-23 09:13:29 GMT` or `Sunday, 06-Nov-94 08:49:2037 GMT` dates.
 /// 
+/// This is a strict parser, see [parse_cookie_date](crate::parse_cookie_date) for the user agent algorithm.
+/// 
 pub fn parse_rfc_850_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(DATE_FORMAT_850).unwrap();
@@ -35,7 +37,7 @@ pub fn parse_rfc_850_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
             "Oct" => 10,
             "Nov" => 11,
             "Dec" => 12,
-            _ => return Err(ParseError::new("Invalid date"))
+            _ => return Err(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))
         };
 
         let mut year: i32 = captures.get(4).unwrap().as_str().parse().unwrap();
@@ -47,13 +49,13 @@ pub fn parse_rfc_850_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
         let secs : u32 = captures.get(7).unwrap().as_str().parse().unwrap();
 
         let naive = NaiveDate::from_ymd_opt(year, month, day)
-            .ok_or(ParseError::new("Invalid date"))?
+            .ok_or(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))?
             .and_hms_opt(hour,min,secs)
-            .ok_or(ParseError::new("Invalid date"))?;
+            .ok_or(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"))?;
 
         return Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
     } else {
-        return Err(ParseError::new("Invalid date"));
+        return Err(ParseError::new(ParseErrorKind::InvalidDate, "Invalid date"));
     }
 }
 
@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+// Directive tests use `assert!(false, ...)` for unexpected variants and zero-padded dates
+#![allow(clippy::assertions_on_constants, clippy::zero_prefixed_literal)]
+
 use crate::*;
 use chrono::{DateTime, Utc, NaiveDate};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 macro_rules! assert_invalid_data {
     ($a: expr) => {
@@ -365,9 +368,37 @@ fn test_parse_cookie_max_age_wrong1() {
     assert_invalid_data!(result);
 }
 
+#[test]
+fn test_parse_cookie_max_age_right4() {
+    let right = "Max-Age=-1200";  // negative values expire at once
+    let result = CookieDirective::from_str(right);
+
+    assert!(result.is_ok());
+
+    if let CookieDirective::MaxAge(seconds) = result.unwrap() {
+        assert_eq!(seconds, Duration::from_secs(0));
+    } else {
+        panic!()
+    }
+}
+
+#[test]
+fn test_parse_cookie_max_age_right5() {
+    let right = "Max-Age=999999999999999999999999";  // too large values are the maximum
+    let result = CookieDirective::from_str(right);
+
+    assert!(result.is_ok());
+
+    if let CookieDirective::MaxAge(seconds) = result.unwrap() {
+        assert_eq!(seconds, Duration::from_secs(u64::MAX));
+    } else {
+        panic!()
+    }
+}
+
 #[test]
 fn test_parse_cookie_max_age_wrong2() {
-    let right = "Max-Age=-1200";
+    let right = "Max-Age=+1200";
     let result = CookieDirective::from_str(right);
 
     assert_invalid_data!(result);
@@ -498,4 +529,717 @@ fn test_cookie_eq4() {
     let cookie1 = Cookie::new("cookie1", "122234");
     let cookie2 = Cookie::from_str("cookie1=1222343").unwrap();
     assert_ne!(&cookie1, &cookie2);
-}
\ No newline at end of file
+}
+
+/// Domain-match conformance matrix: (request host, cookie domain, expected result)
+const DOMAIN_MATCH_MATRIX: [(&str, &str, bool); 24] = [
+    // Identical
+    ("example.com", "example.com", true),
+    // Case-insensitive
+    ("EXAMPLE.com", "example.COM", true),
+    ("www.Example.com", "example.com", true),
+    // Subdomains
+    ("www.example.com", "example.com", true),
+    ("a.b.example.com", "example.com", true),
+    // Leading dot at the cookie domain is ignored
+    ("www.example.com", ".example.com", true),
+    ("example.com", ".example.com", true),
+    // Suffix collisions
+    ("example.com.evil.org", "example.com", false),
+    ("notexample.com", "example.com", false),
+    ("www.notexample.com", "example.com", false),
+    ("example.comx", "example.com", false),
+    ("xexample.com", "example.com", false),
+    // Cookie domain is a subdomain of request host
+    ("example.com", "www.example.com", false),
+    // Domain that merely contains the host
+    ("ample.com", "example.com", false),
+    ("example", "example.com", false),
+    // Empty values
+    ("example.com", "", false),
+    ("", "example.com", false),
+    ("example.com", ".", false),
+    // IP addresses only match when identical
+    ("192.168.0.1", "192.168.0.1", true),
+    ("192.168.0.1", "168.0.1", false),
+    ("192.168.0.1", "0.1", false),
+    ("[::1]", "[::1]", true),
+    ("::ffff:10.0.0.1", "0.0.1", false),
+    ("10.0.0.1.example.com", "example.com", true),
+];
+
+#[test]
+fn test_domain_match_matrix() {
+    for (host, domain, expected) in DOMAIN_MATCH_MATRIX {
+        assert_eq!(domain_match(host, domain), expected, "domain_match({:?}, {:?})", host, domain);
+    }
+}
+
+#[test]
+fn test_cookie_match_matrix() {
+    for (host, domain, expected) in DOMAIN_MATCH_MATRIX {
+        let mut cookie = SetCookie::new("cookie1", "122343");
+        cookie.domain = Some(String::from(domain));
+        assert_eq!(cookie.use_in_request_domain(host), expected, "use_in_request_domain({:?}) with Domain={:?}", host, domain);
+    }
+}
+
+#[test]
+fn test_cookie_match7() {
+    let cookie1 = SetCookie::from_str("cookie1=122343; Domain=example.com");
+    assert!(cookie1.is_ok());
+    assert!(!cookie1.unwrap().use_in_request_domain("example.com.evil.org"));
+}
+
+#[test]
+fn test_is_ip_address() {
+    assert!(is_ip_address("127.0.0.1"));
+    assert!(is_ip_address("::1"));
+    assert!(is_ip_address("[2001:db8::1]"));
+    assert!(!is_ip_address("example.com"));
+    assert!(!is_ip_address("1.2.3.4.example.com"));
+}
+
+#[test]
+fn test_cookie_match_public_suffix() {
+    let cookie1 = SetCookie::from_str("cookie1=122343; Domain=com").unwrap();
+    assert!(!cookie1.use_in_request_domain("example.com"));
+    assert!(cookie1.use_in_request_domain("com"));
+
+    let cookie2 = SetCookie::from_str("cookie1=122343; Domain=.co.uk").unwrap();
+    assert!(!cookie2.use_in_request_domain("www.example.co.uk"));
+}
+
+#[test]
+fn test_cookie_from_response1() {
+    let cookie = SetCookie::from_response("cookie1=122343", "https://www.example.com/login").unwrap();
+
+    assert!(cookie.host_only);
+    assert_eq!(cookie.domain.as_deref(), Some("www.example.com"));
+    assert!(cookie.use_in_request_domain("www.example.com"));
+    assert!(cookie.use_in_request_domain("WWW.example.com"));
+    assert!(!cookie.use_in_request_domain("a.www.example.com"));
+    assert!(!cookie.to_string().contains("Domain"));
+}
+
+#[test]
+fn test_cookie_from_response2() {
+    let cookie = SetCookie::from_response("cookie1=122343; Domain=.Example.com", "https://www.example.com/").unwrap();
+
+    assert!(!cookie.host_only);
+    assert_eq!(cookie.domain.as_deref(), Some("example.com"));
+    assert!(cookie.use_in_request_domain("a.example.com"));
+}
+
+#[test]
+fn test_cookie_from_response_wrong1() {
+    let result = SetCookie::from_response("cookie1=122343; Domain=other.com", "https://www.example.com/");
+    assert_invalid_data!(result, "Cookie domain other.com does not match request host www.example.com");
+
+    let result = SetCookie::from_response("cookie1=122343; Domain=a.www.example.com", "https://www.example.com/");
+    assert_invalid_data!(result);
+}
+
+#[test]
+fn test_cookie_from_response_wrong2() {
+    let result = SetCookie::from_response("cookie1=122343; Domain=co.uk", "https://www.example.co.uk/");
+    assert_invalid_data!(result, "Cookie domain co.uk is a public suffix");
+}
+
+#[test]
+fn test_cookie_from_response_wrong3() {
+    assert_invalid_data!(SetCookie::from_response("cookie1=122343", "www.example.com"));
+}
+
+#[test]
+fn test_default_path() {
+    assert_eq!(default_path("/"), "/");
+    assert_eq!(default_path("/login"), "/");
+    assert_eq!(default_path("/app/login"), "/app");
+    assert_eq!(default_path("/app/admin/"), "/app/admin");
+    assert_eq!(default_path("//login"), "/");
+    assert_eq!(default_path("login"), "/");
+    assert_eq!(default_path(""), "/");
+}
+
+#[test]
+fn test_cookie_from_response_with_list() {
+    let list = PublicSuffixList::parse("com\nexample.com");
+
+    assert!(SetCookie::from_response("id=1; Domain=example.com", "https://www.example.com/").is_ok());
+    assert!(SetCookie::from_response_with_list("id=1; Domain=example.com", "https://www.example.com/", &list).is_err());
+
+    let mut cookie = SetCookie::from_str("id=1").unwrap();
+    cookie.domain = Some(String::from("example.com"));
+    assert!(cookie.use_in_request_domain("www.example.com"));
+    assert!(!cookie.use_in_request_domain_with_list("www.example.com", &list));
+    assert!(cookie.use_in_request_domain_with_list("example.com", &list));
+}
+
+#[test]
+fn test_cookie_from_response_path() {
+    let cookie = SetCookie::from_response("cookie1=122343", "https://example.com/app/login?next=/admin").unwrap();
+    assert_eq!(cookie.path.as_deref(), Some("/app"));
+    assert!(cookie.use_in_request_path("/app/index"));
+    assert!(!cookie.use_in_request_path("/admin"));
+
+    let cookie = SetCookie::from_response("cookie1=122343; Path=app", "https://example.com/app/login").unwrap();
+    assert_eq!(cookie.path.as_deref(), Some("/app"));
+
+    let cookie = SetCookie::from_response("cookie1=122343; Path=/", "https://example.com/app/login").unwrap();
+    assert_eq!(cookie.path.as_deref(), Some("/"));
+}
+
+fn cross_site_context(url: &str, site_for_cookies: &str, kind: RequestKind, method: &str) -> RequestContext {
+    let mut context = RequestContext::new(url).unwrap();
+    context.site_for_cookies = Some(RequestUrl::from_str(site_for_cookies).unwrap());
+    context.kind = kind;
+    context.method = String::from(method);
+    context
+}
+
+#[test]
+fn test_cookie_same_site_strict() {
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com; SameSite=Strict").unwrap();
+
+    // Strict cookies are sent to subdomains at same-site requests
+    assert!(cookie.use_in_request("www.example.com", "/", false));
+    assert!(cookie.use_in_context(&RequestContext::new("https://api.example.com/").unwrap()));
+
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Navigation, "GET");
+    assert!(!cookie.use_in_context(&context));
+}
+
+#[test]
+fn test_cookie_same_site_lax() {
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com; SameSite=Lax").unwrap();
+
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Navigation, "GET");
+    assert!(cookie.use_in_context(&context));
+
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Navigation, "POST");
+    assert!(!cookie.use_in_context(&context));
+
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Subresource, "GET");
+    assert!(!cookie.use_in_context(&context));
+
+    let context = cross_site_context("https://www.example.com/", "https://example.com/", RequestKind::Subresource, "POST");
+    assert!(cookie.use_in_context(&context));
+}
+
+#[test]
+fn test_cookie_same_site_none() {
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com; SameSite=None; Secure").unwrap();
+
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Subresource, "POST");
+    assert!(cookie.use_in_context(&context));
+
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com; SameSite=None").unwrap();
+    assert!(!cookie.use_in_context(&context));
+}
+
+#[test]
+fn test_cookie_schemeful_same_site() {
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com; SameSite=Lax").unwrap();
+    let policy = CookiePolicy { schemeful_same_site: true, ..CookiePolicy::default() };
+
+    // Subresource of the http version of the site
+    let context = cross_site_context("https://www.example.com/", "http://example.com/", RequestKind::Subresource, "GET");
+    assert!(cookie.use_in_context(&context));
+    assert!(!cookie.use_in_context_with_policy(&context, &policy));
+
+    let context = cross_site_context("https://www.example.com/", "https://example.com/", RequestKind::Subresource, "GET");
+    assert!(cookie.use_in_context_with_policy(&context, &policy));
+
+    // Cross-scheme top-level navigations are treated as cross-site navigations
+    let context = cross_site_context("https://www.example.com/", "http://example.com/", RequestKind::Navigation, "GET");
+    assert!(cookie.use_in_context_with_policy(&context, &policy));
+}
+
+#[test]
+fn test_cookie_same_site_unspecified() {
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com").unwrap();
+    assert_eq!(cookie.same_site, SameSiteValue::Unspecified);
+    assert_eq!(SetCookie::new("id", "1").same_site, SameSiteValue::Unspecified);
+
+    // Lax by default
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Navigation, "GET");
+    assert!(cookie.use_in_context(&context));
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Subresource, "GET");
+    assert!(!cookie.use_in_context(&context));
+
+    // Legacy browsers, it does not require Secure
+    let policy = CookiePolicy { default_same_site: SameSiteValue::None, ..CookiePolicy::default() };
+    assert!(cookie.use_in_context_with_policy(&context, &policy));
+
+    let policy = CookiePolicy { default_same_site: SameSiteValue::Unspecified, ..CookiePolicy::default() };
+    assert!(!cookie.use_in_context_with_policy(&context, &policy));
+}
+
+#[test]
+fn test_cookie_lax_allowing_unsafe() {
+    let policy = CookiePolicy { lax_allowing_unsafe: Some(LAX_ALLOWING_UNSAFE_MAX_AGE), ..CookiePolicy::default() };
+    let post = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Navigation, "POST");
+
+    let mut cookie = SetCookie::from_str("cookie1=122343; Domain=example.com").unwrap();
+    assert!(!cookie.use_in_context(&post));
+    assert!(cookie.use_in_context_with_policy(&post, &policy));
+
+    // Only top-level navigations
+    let context = cross_site_context("https://www.example.com/", "https://other.org/", RequestKind::Subresource, "POST");
+    assert!(!cookie.use_in_context_with_policy(&context, &policy));
+
+    // Only recent cookies
+    cookie.set_created(SystemTime::now() - Duration::from_secs(121));
+    assert!(!cookie.use_in_context_with_policy(&post, &policy));
+
+    // Only cookies without SameSite
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com; SameSite=Lax").unwrap();
+    assert!(!cookie.use_in_context_with_policy(&post, &policy));
+}
+
+#[test]
+fn test_cookie_use_in_context_expired() {
+    let cookie = SetCookie::from_str("cookie1=122343; Domain=example.com; Expires=Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+
+    assert!(!cookie.use_in_context(&RequestContext::new("https://example.com/").unwrap()));
+}
+
+#[test]
+fn test_cookie_display1() {
+    let cookie = SetCookie::from_str("id=a3fWa; Domain=example.com; Path=/app; Expires=Wed, 21 Oct 2015 07:28:00 GMT; SameSite=Strict; Secure; HttpOnly").unwrap();
+
+    assert_eq!(cookie.to_string(),
+        "id=a3fWa; Domain=example.com; Path=/app; Expires=Wed, 21 Oct 2015 07:28:00 GMT; SameSite=Strict; Secure; HttpOnly");
+}
+
+#[test]
+fn test_cookie_display2() {
+    let cookie = SetCookie::from_str("id=a3fWa; Expires=Sunday, 06-Nov-94 08:49:37 GMT").unwrap();
+
+    assert_eq!(cookie.to_string(), "id=a3fWa; Expires=Sun, 06 Nov 1994 08:49:37 GMT");
+}
+
+#[test]
+fn test_cookie_display_max_age() {
+    let cookie = SetCookie::from_str("id=a3fWa; Max-Age=3600; Expires=Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+
+    assert_eq!(cookie.to_string(), "id=a3fWa; Max-Age=3600");
+
+    let legacy = cookie.serialize(SerializeOptions { legacy_expires: true });
+    let expires = DateTime::<Utc>::from(cookie.expire_time().unwrap()).format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+    assert_eq!(legacy, format!("id=a3fWa; Max-Age=3600; Expires={}", expires));
+
+    let parsed = SetCookie::from_str(&legacy).unwrap();
+    assert_eq!(parsed.max_age, Some(Duration::from_secs(3600)));
+    assert!(parsed.expires.is_some());
+}
+
+#[test]
+fn test_cookie_round_trip() {
+    let mut cookie = SetCookie::new("id", "a3fWa");
+    cookie.domain = Some(String::from("example.com"));
+    cookie.path = Some(String::from("/app"));
+    let naive = NaiveDate::from_ymd_opt(2024, 7, 8).unwrap().and_hms_opt(9, 10, 11).unwrap();
+    cookie.expires = Some(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
+    cookie.same_site = SameSiteValue::None;
+    cookie.secure = true;
+    cookie.http_only = true;
+    cookie.partitioned = true;
+    cookie.extensions.insert(String::from("ext"), None);
+
+    let parsed = SetCookie::from_str(&cookie.to_string()).unwrap();
+
+    assert_eq!(parsed, cookie);
+    assert_eq!(parsed.expires, cookie.expires);
+    assert_eq!(parsed.max_age, cookie.max_age);
+    assert_eq!(parsed.same_site, cookie.same_site);
+    assert_eq!(parsed.secure, cookie.secure);
+    assert_eq!(parsed.http_only, cookie.http_only);
+    assert!(parsed.partitioned);
+    assert_eq!(parsed.extensions, cookie.extensions);
+}
+
+#[test]
+fn test_cookie_round_trip_same_site() {
+    for same_site in [SameSiteValue::Strict, SameSiteValue::Lax, SameSiteValue::None, SameSiteValue::Unspecified] {
+        let mut cookie = SetCookie::new("id", "a3fWa");
+        cookie.same_site = same_site;
+
+        let parsed = SetCookie::from_str(&cookie.to_string()).unwrap();
+        assert_eq!(parsed, cookie);
+        assert_eq!(parsed.same_site, same_site);
+    }
+}
+
+#[test]
+fn test_cookie_validate1() {
+    let mut cookie = SetCookie::new("id", "\"a3fWa\"");
+    cookie.domain = Some(String::from(".example.com"));
+    cookie.path = Some(String::from("/app"));
+    cookie.extensions.insert(String::from("comment"), Some(String::from("session")));
+
+    assert!(cookie.validate().is_ok());
+    assert_eq!(cookie.try_to_string().unwrap(), cookie.to_string());
+}
+
+#[test]
+fn test_cookie_expires_out_of_range() {
+    let mut cookie = SetCookie::new("id", "1");
+    let naive = NaiveDate::from_ymd_opt(11476, 8, 15).unwrap().and_hms_opt(5, 20, 0).unwrap();
+    cookie.expires = Some(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
+    assert_eq!(cookie.to_string(), "id=1; Expires=Fri, 31 Dec 9999 23:59:59 GMT");
+
+    let naive = NaiveDate::from_ymd_opt(999, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    cookie.expires = Some(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc));
+    assert_eq!(cookie.to_string(), "id=1; Expires=Thu, 01 Jan 1970 00:00:00 GMT");
+}
+
+#[test]
+fn test_cookie_validate_reserved_extension() {
+    let mut cookie = SetCookie::new("id", "1");
+    cookie.priority = CookiePriority::High;
+    cookie.extensions.insert(String::from("priority"), Some(String::from("Low")));
+
+    assert_eq!(cookie.validate(), Err(ValidationError::ReservedExtension(String::from("priority"))));
+    assert_eq!(cookie.to_string(), "id=1; Priority=High");
+}
+
+#[test]
+fn test_cookie_validate_injection() {
+    let cookie = SetCookie::new("a", "x\r\nSet-Cookie: admin=1");
+    assert_invalid_data!(cookie.try_to_string(), "Invalid cookie value: \"x\\r\\nSet-Cookie: admin=1\"");
+
+    let cookie = SetCookie::new("a\r\nX-Header: 1", "x");
+    assert_eq!(cookie.validate(), Err(ValidationError::InvalidName(String::from("a\r\nX-Header: 1"))));
+
+    let cookie = SetCookie::new("a=b", "x");
+    assert!(cookie.validate().is_err());
+}
+
+#[test]
+fn test_cookie_validate_attributes() {
+    let mut cookie = SetCookie::new("id", "1");
+    cookie.domain = Some(String::from("example.com; Secure"));
+    assert_eq!(cookie.validate(), Err(ValidationError::InvalidDomain(String::from("example.com; Secure"))));
+
+    let mut cookie = SetCookie::new("id", "1");
+    cookie.path = Some(String::from("/\r\n"));
+    assert_eq!(cookie.validate(), Err(ValidationError::InvalidPath(String::from("/\r\n"))));
+
+    let mut cookie = SetCookie::new("id", "1");
+    cookie.extensions.insert(String::from("ext"), Some(String::from("a;b")));
+    assert_eq!(cookie.try_serialize(SerializeOptions::default()), Err(ValidationError::InvalidExtension(String::from("ext"))));
+}
+
+#[test]
+fn test_request_cookie_validate() {
+    assert!(Cookie::new("id", "1").validate().is_ok());
+    assert!(Cookie::new("id", "1; admin=1").validate().is_err());
+    assert!(Cookie::new("", "1").validate().is_err());
+}
+
+#[test]
+fn test_parse_lenient1() {
+    let cookie = SetCookie::parse("id=a3fWa; Max-Age=abc; SameSite=bogus; Domain; Expires=tomorrow; Secure", ParseMode::Lenient).unwrap();
+
+    assert_eq!(cookie.name, "id");
+    assert_eq!(cookie.value, "a3fWa");
+    assert_eq!(cookie.max_age, None);
+    assert_eq!(cookie.expires, None);
+    assert_eq!(cookie.domain, None);
+    assert_eq!(cookie.same_site, SameSiteValue::Unspecified);
+    assert!(cookie.secure);
+
+    assert!(SetCookie::parse("id=a3fWa; Max-Age=abc", ParseMode::Strict).is_err());
+    assert!(SetCookie::parse("id=a3fWa; SameSite=bogus", ParseMode::Strict).is_err());
+    assert!(SetCookie::parse("id=a3fWa; Domain", ParseMode::Strict).is_err());
+}
+
+#[test]
+fn test_parse_domain_leading_dots() {
+    for mode in [ParseMode::Strict, ParseMode::Lenient] {
+        let cookie = SetCookie::parse("id=1; Domain=.example.com", mode).unwrap();
+        assert_eq!(cookie.domain.as_deref(), Some("example.com"));
+
+        let cookie = SetCookie::parse("id=1; Domain=..example.com", mode).unwrap();
+        assert_eq!(cookie.domain.as_deref(), Some(".example.com"));
+    }
+
+    assert!(SetCookie::from_response("id=1; Domain=..example.com", "https://www.example.com/").is_err());
+    assert!(SetCookie::from_response("id=1; Domain=.example.com", "https://www.example.com/").is_ok());
+}
+
+#[test]
+fn test_parse_lenient_last_wins() {
+    let cookie = SetCookie::parse("id=1; Domain=a.com; Path=/a; Domain=B.com; Path=/b; Max-Age=10; Max-Age=20", ParseMode::Lenient).unwrap();
+
+    assert_eq!(cookie.domain.as_deref(), Some("b.com"));
+    assert_eq!(cookie.path.as_deref(), Some("/b"));
+    assert_eq!(cookie.max_age, Some(Duration::from_secs(20)));
+
+    // Invalid SameSite value resets the default
+    let cookie = SetCookie::parse("id=1; SameSite=Strict; SameSite=bogus", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.same_site, SameSiteValue::Unspecified);
+
+    // Invalid path resets the default path
+    let cookie = SetCookie::parse("id=1; Path=/a; Path=b", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.path, None);
+}
+
+#[test]
+fn test_parse_lenient_values() {
+    let cookie = SetCookie::parse("  id = ; secure=yes; HTTPONLY; ; Priority=High; Partitioned", ParseMode::Lenient).unwrap();
+
+    assert_eq!(cookie.name, "id");
+    assert_eq!(cookie.value, "");
+    assert!(cookie.secure);
+    assert!(cookie.http_only);
+    assert_eq!(cookie.priority, CookiePriority::High);
+    assert!(cookie.partitioned);
+    assert!(cookie.extensions.is_empty());
+}
+
+#[test]
+fn test_parse_lenient_max_age() {
+    let cookie = SetCookie::parse("id=1; Max-Age=-1", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.max_age, Some(Duration::from_secs(0)));
+
+    let cookie = SetCookie::parse("id=1; Max-Age=+10", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.max_age, None);
+
+    let cookie = SetCookie::parse("id=1; Max-Age=1 0", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.max_age, None);
+}
+
+#[test]
+fn test_parse_lenient_wrong() {
+    assert!(SetCookie::parse("id", ParseMode::Lenient).is_err());
+    assert!(SetCookie::parse("=1; Secure", ParseMode::Lenient).is_err());
+    assert!(SetCookie::parse("; id=1", ParseMode::Lenient).is_err());
+}
+
+#[test]
+fn test_parse_error_kind() {
+    let kind = |s: &str| SetCookie::from_str(s).err().unwrap().kind();
+
+    assert_eq!(kind("name:value"), ParseErrorKind::Malformed);
+    assert_eq!(kind("=value"), ParseErrorKind::MissingName);
+    assert_eq!(kind("id="), ParseErrorKind::EmptyValue);
+    assert_eq!(kind("id=1; Expires=yesterday"), ParseErrorKind::InvalidDate);
+    assert_eq!(kind("id=1; Max-Age=1A"), ParseErrorKind::InvalidMaxAge);
+    assert_eq!(kind("id=1; SameSite=Always"), ParseErrorKind::InvalidSameSite);
+    assert_eq!(kind("id=1; Path"), ParseErrorKind::DirectiveNeedsValue);
+    assert_eq!(kind("id=1; Domain= "), ParseErrorKind::DirectiveNeedsValue);
+}
+
+#[test]
+fn test_parse_error_span() {
+    let span = |s: &'static str| {
+        let error = SetCookie::from_str(s).err().unwrap();
+        &s[error.span().unwrap()]
+    };
+
+    assert_eq!(span(" name:value ; Path=/"), "name:value");
+    assert_eq!(span("id=1; Path=/; SameSite = Always ; Secure"), "Always");
+    assert_eq!(span("id=1; Expires=Wed, 32 Oct 2021 07:28:00 GMT"), "Wed, 32 Oct 2021 07:28:00 GMT");
+    assert_eq!(span("id=1;  Domain ;Secure"), "Domain");
+}
+
+#[test]
+fn test_parse_warnings() {
+    let input = "id=1; Path=/; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Comment=none; path=/app";
+    let (cookie, warnings) = SetCookie::parse_with_warnings(input, ParseMode::Strict).unwrap();
+
+    assert_eq!(cookie.path.as_deref(), Some("/app"));
+    assert_eq!(warnings.len(), 3);
+
+    assert_eq!(warnings[0].kind, ParseWarningKind::ExpiresInPast);
+    assert_eq!(&input[warnings[0].span.clone()], "Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+    assert_eq!(warnings[1].kind, ParseWarningKind::UnknownAttribute);
+    assert_eq!(warnings[1].attribute, "comment");
+    assert_eq!(warnings[2].kind, ParseWarningKind::DuplicateAttribute);
+    assert_eq!(warnings[2].attribute, "path");
+    assert_eq!(&input[warnings[2].span.clone()], "path=/app");
+}
+
+#[test]
+fn test_parse_warnings_at() {
+    let input = "id=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT";
+    let expires = SystemTime::UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+
+    for mode in [ParseMode::Strict, ParseMode::Lenient] {
+        let (cookie, warnings) = SetCookie::parse_with_warnings_at(input, mode, expires - Duration::from_secs(1)).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(cookie.created(), expires - Duration::from_secs(1));
+
+        let (_, warnings) = SetCookie::parse_with_warnings_at(input, mode, expires + Duration::from_secs(1)).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ParseWarningKind::ExpiresInPast);
+    }
+}
+
+#[test]
+fn test_parse_warnings_lenient() {
+    let input = "id=1; Domain=; Max-Age=soon; SameSite=Always; Secure; SECURE";
+    let (cookie, warnings) = SetCookie::parse_with_warnings(input, ParseMode::Lenient).unwrap();
+
+    assert!(cookie.secure);
+    let kinds: Vec<(ParseWarningKind, &str)> = warnings.iter().map(|w| (w.kind, w.attribute.as_str())).collect();
+    assert_eq!(kinds, vec![
+        (ParseWarningKind::IgnoredAttribute, "domain"),
+        (ParseWarningKind::IgnoredAttribute, "max-age"),
+        (ParseWarningKind::IgnoredAttribute, "samesite"),
+        (ParseWarningKind::DuplicateAttribute, "secure")
+    ]);
+    assert_eq!(&input[warnings[3].span.clone()], "SECURE");
+
+    let (_, warnings) = SetCookie::parse_with_warnings("id=1; Path=/; Secure", ParseMode::Lenient).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_parse_lenient_expires() {
+    let expected = DateTime::<Utc>::from_naive_utc_and_offset(
+        NaiveDate::from_ymd_opt(2015, 10, 21).unwrap().and_hms_opt(7, 28, 0).unwrap(), Utc);
+
+    let cookie = SetCookie::parse("id=1; Expires=Wed, 21-Oct-2015 07:28:00 GMT", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.expires, Some(expected));
+
+    let cookie = SetCookie::parse("id=1; Expires=21 Oct 2015 07:28:00", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.expires, Some(expected));
+
+    let cookie = SetCookie::parse("id=1; Expires=Wed, 21 Oct 2015 23:59:60 GMT", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.expires, None);
+}
+
+#[test]
+fn test_max_age_expire_immediately() {
+    for header in ["id=1; Max-Age=0", "id=1; Max-Age=-1", "id=1; Max-Age=-99999999999999999999"] {
+        let cookie = SetCookie::from_str(header).unwrap();
+
+        assert_eq!(cookie.max_age, Some(Duration::ZERO));
+        assert_eq!(cookie.expire_time(), Some(std::time::SystemTime::UNIX_EPOCH));
+        assert!(cookie.expired());
+        assert_eq!(cookie.to_string(), "id=1; Max-Age=0");
+    }
+}
+
+#[test]
+fn test_max_age_overflow() {
+    let cookie = SetCookie::from_str("id=1; Max-Age=18446744073709551615").unwrap();
+
+    assert!(!cookie.expired());
+    let expected = "id=1; Max-Age=18446744073709551615; Expires=Fri, 31 Dec 9999 23:59:59 GMT";
+    assert_eq!(cookie.serialize(SerializeOptions { legacy_expires: true }), expected);
+}
+
+#[test]
+fn test_parse_at() {
+    let created = std::time::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let mut cookie = SetCookie::parse_at("id=1; Max-Age=100", ParseMode::Lenient, created).unwrap();
+
+    assert_eq!(cookie.created(), created);
+    assert_eq!(cookie.expire_time(), Some(created + Duration::from_secs(100)));
+    assert!(!cookie.expired_at(created + Duration::from_secs(99)));
+    assert!(cookie.expired_at(created + Duration::from_secs(100)));
+    assert!(cookie.expired());
+
+    let restored = std::time::SystemTime::now();
+    cookie.set_created(restored);
+    assert_eq!(cookie.expire_time(), Some(restored + Duration::from_secs(100)));
+    assert!(!cookie.expired());
+}
+
+#[test]
+fn test_prefix_from_response() {
+    let url = "https://www.example.com/app/login";
+
+    assert!(SetCookie::from_response("__Secure-id=1; Secure", url).is_ok());
+    assert!(SetCookie::from_response("__Host-id=1; Secure; Path=/", url).unwrap().host_only);
+
+    let rejected = [
+        "__Secure-id=1",
+        "__secure-id=1; Domain=example.com",
+        "__Host-id=1; Path=/",
+        "__Host-id=1; Secure",
+        "__Host-id=1; Secure; Path=/app",
+        "__HOST-id=1; Secure; Path=/; Domain=example.com",
+        "__Host-id=1; Secure; Path=/; Domain=www.example.com"
+    ];
+    for header in rejected {
+        let error = SetCookie::from_response(header, url).err().unwrap();
+        assert_eq!(error.kind(), ParseErrorKind::InvalidPrefix, "{}", header);
+    }
+}
+
+#[test]
+fn test_prefix_warning() {
+    let input = " __Host-id=1; Secure; Domain=example.com";
+    let (_, warnings) = SetCookie::parse_with_warnings(input, ParseMode::Lenient).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, ParseWarningKind::InvalidPrefix);
+    assert_eq!(&input[warnings[0].span.clone()], "__Host-id=1");
+
+    let (_, warnings) = SetCookie::parse_with_warnings("__Secure-id=1; Secure", ParseMode::Strict).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_prefix_validation() {
+    let mut cookie = SetCookie::new("__Secure-id", "1");
+    assert_eq!(cookie.validate(), Err(ValidationError::InvalidPrefix(String::from("__Secure-id"))));
+    cookie.secure = true;
+    assert_eq!(cookie.validate(), Ok(()));
+
+    let mut cookie = SetCookie::new_host_prefixed("id", "1");
+    assert_eq!(cookie.name, "__Host-id");
+    assert_eq!(cookie.validate(), Ok(()));
+    cookie.domain = Some(String::from("example.com"));
+    assert!(cookie.try_to_string().is_err());
+
+    assert_eq!(SetCookie::new_host_prefixed("__host-id", "1").name, "__host-id");
+}
+
+#[test]
+fn test_partitioned() {
+    let cookie = SetCookie::from_str("id=1; Secure; Partitioned").unwrap();
+    assert!(cookie.partitioned);
+    assert_eq!(cookie.to_string(), "id=1; Secure; Partitioned");
+    assert_eq!(cookie.validate(), Ok(()));
+
+    let mut cookie = SetCookie::new("id", "1");
+    cookie.partitioned = true;
+    assert_eq!(cookie.validate(), Err(ValidationError::PartitionedNotSecure(String::from("id"))));
+
+    let error = SetCookie::from_response("id=1; Partitioned", "https://example.com/").err().unwrap();
+    assert_eq!(error.kind(), ParseErrorKind::PartitionedNotSecure);
+}
+
+#[test]
+fn test_priority() {
+    assert_eq!(SetCookie::from_str("id=1").unwrap().priority, CookiePriority::Medium);
+    assert_eq!(SetCookie::from_str("id=1; priority=LOW").unwrap().priority, CookiePriority::Low);
+    assert_eq!(SetCookie::from_str("id=1; Priority=High").unwrap().priority, CookiePriority::High);
+
+    let error = SetCookie::from_str("id=1; Priority=Urgent").err().unwrap();
+    assert_eq!(error.kind(), ParseErrorKind::InvalidPriority);
+    assert!(SetCookie::from_str("id=1; Priority").is_err());
+
+    let (cookie, warnings) = SetCookie::parse_with_warnings("id=1; Priority=Urgent", ParseMode::Lenient).unwrap();
+    assert_eq!(cookie.priority, CookiePriority::Medium);
+    assert_eq!(warnings[0].kind, ParseWarningKind::IgnoredAttribute);
+
+    assert!(CookiePriority::Low < CookiePriority::Medium && CookiePriority::Medium < CookiePriority::High);
+}
+
+#[test]
+fn test_priority_display() {
+    let mut cookie = SetCookie::new("id", "1");
+    assert_eq!(cookie.to_string(), "id=1");
+
+    cookie.priority = CookiePriority::High;
+    assert_eq!(cookie.to_string(), "id=1; Priority=High");
+    assert_eq!(SetCookie::from_str(&cookie.to_string()).unwrap().priority, CookiePriority::High);
+}
@@ -0,0 +1,168 @@
+// Copyright 2021 Juan A. Cáceres (cacexp@gmail.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::fmt;
+use crate::{COOKIE_DOMAIN, COOKIE_EXPIRES, COOKIE_HTTP_ONLY, COOKIE_MAX_AGE, COOKIE_PARTITIONED, COOKIE_PATH,
+    COOKIE_PRIORITY, COOKIE_SAME_SITE, COOKIE_SECURE};
+
+/// Error produced when a cookie cannot be safely written in a header, see
+/// [SetCookie::validate](crate::SetCookie::validate)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The name is not a token
+    InvalidName(String),
+    /// The value has characters other than `cookie-octet`
+    InvalidValue(String),
+    /// The domain is not a host name
+    InvalidDomain(String),
+    /// The path has control characters or `;`
+    InvalidPath(String),
+    /// An extension has control characters or `;`
+    InvalidExtension(String),
+    /// An extension is named after an attribute with its own field, such as `Priority`
+    ReservedExtension(String),
+    /// The attributes do not meet the requirements of the name prefix, see [CookiePrefix](crate::CookiePrefix)
+    InvalidPrefix(String),
+    /// The cookie is `Partitioned` but it is not `Secure`
+    PartitionedNotSecure(String)
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::InvalidName(name) => write!(f, "Invalid cookie name: {:?}", name),
+            ValidationError::InvalidValue(value) => write!(f, "Invalid cookie value: {:?}", value),
+            ValidationError::InvalidDomain(domain) => write!(f, "Invalid cookie domain: {:?}", domain),
+            ValidationError::InvalidPath(path) => write!(f, "Invalid cookie path: {:?}", path),
+            ValidationError::InvalidExtension(extension) => write!(f, "Invalid cookie extension: {:?}", extension),
+            ValidationError::ReservedExtension(extension) => write!(f, "Reserved cookie extension: {:?}", extension),
+            ValidationError::InvalidPrefix(name) => write!(f, "Cookie prefix requirements not met: {:?}", name),
+            ValidationError::PartitionedNotSecure(name) => write!(f, "Partitioned cookie is not secure: {:?}", name)
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Checks if `name` is a `token` as defined in [RFC2616 Section 2.2](https://datatracker.ietf.org/doc/html/rfc2616#section-2.2):
+/// one or more characters excluding controls and separators.
+pub(crate) fn is_token(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|c| {
+        c.is_ascii() && !c.is_ascii_control() && !b"()<>@,;:\\\"/[]?={} \t".contains(&c)
+    })
+}
+
+/// Checks if `c` is a `cookie-octet` as defined in [RFC6265 Section 4.1.1](https://datatracker.ietf.org/doc/html/rfc6265#section-4.1.1):
+/// US-ASCII characters excluding controls, whitespace, `"`, `,`, `;` and `\`.
+fn is_cookie_octet(c: u8) -> bool {
+    c == 0x21 || (0x23..=0x2B).contains(&c) || (0x2D..=0x3A).contains(&c)
+        || (0x3C..=0x5B).contains(&c) || (0x5D..=0x7E).contains(&c)
+}
+
+/// Checks if `value` is a `cookie-value`: zero or more `cookie-octet`, optionally enclosed in double quotes
+pub(crate) fn is_cookie_value(value: &str) -> bool {
+    let unquoted = match value.strip_prefix('"') {
+        Some(rest) => match rest.strip_suffix('"') {
+            Some(inner) => inner,
+            None => return false
+        },
+        None => value
+    };
+    return unquoted.bytes().all(is_cookie_octet);
+}
+
+/// Checks if `value` is an `av-octet` sequence: characters excluding controls and `;`
+pub(crate) fn is_attribute_value(value: &str) -> bool {
+    !value.chars().any(|c| c.is_control() || c == ';')
+}
+
+/// Checks if `name` is, ignoring case, an attribute that [SetCookie](crate::SetCookie) stores in its own field,
+/// so it cannot be an extension.
+pub(crate) fn is_reserved_attribute(name: &str) -> bool {
+    [COOKIE_EXPIRES, COOKIE_MAX_AGE, COOKIE_DOMAIN, COOKIE_PATH, COOKIE_SAME_SITE, COOKIE_SECURE, COOKIE_HTTP_ONLY,
+        COOKIE_PARTITIONED, COOKIE_PRIORITY].iter().any(|attribute| attribute.eq_ignore_ascii_case(name))
+}
+
+/// Checks if `domain` is a host name: labels of letters, digits and hyphens separated by dots.
+/// A leading dot is allowed.
+pub(crate) fn is_domain(domain: &str) -> bool {
+    let domain = domain.strip_prefix('.').unwrap_or(domain);
+    !domain.is_empty() && domain.split('.').all(|label| {
+        !label.is_empty() && label.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate::{is_attribute_value, is_cookie_value, is_domain, is_reserved_attribute, is_token};
+
+    #[test]
+    fn test_token() {
+        assert!(is_token("id"));
+        assert!(is_token("__Host-session_id.v2"));
+        assert!(!is_token(""));
+        assert!(!is_token("a b"));
+        assert!(!is_token("a=b"));
+        assert!(!is_token("a;b"));
+        assert!(!is_token("a\r\nb"));
+        assert!(!is_token("añ"));
+    }
+
+    #[test]
+    fn test_cookie_value() {
+        assert!(is_cookie_value(""));
+        assert!(is_cookie_value("a3fWa"));
+        assert!(is_cookie_value("john@smith"));
+        assert!(is_cookie_value("\"quoted\""));
+        assert!(is_cookie_value("\"\""));
+        assert!(is_cookie_value("YWJj+/=="));
+        assert!(!is_cookie_value("\"unbalanced"));
+        assert!(!is_cookie_value("a\"b"));
+        assert!(!is_cookie_value("a b"));
+        assert!(!is_cookie_value("a,b"));
+        assert!(!is_cookie_value("a;b"));
+        assert!(!is_cookie_value("a\\b"));
+        assert!(!is_cookie_value("x\r\nSet-Cookie: admin=1"));
+    }
+
+    #[test]
+    fn test_attribute_value() {
+        assert!(is_attribute_value("/app path"));
+        assert!(!is_attribute_value("/app;"));
+        assert!(!is_attribute_value("/app\n"));
+    }
+
+    #[test]
+    fn test_domain() {
+        assert!(is_domain("example.com"));
+        assert!(is_domain(".example.com"));
+        assert!(is_domain("my-site.co.uk"));
+        assert!(is_domain("127.0.0.1"));
+        assert!(!is_domain(""));
+        assert!(!is_domain("."));
+        assert!(!is_domain("example..com"));
+        assert!(!is_domain("example.com;"));
+        assert!(!is_domain("exa mple.com"));
+    }
+
+    #[test]
+    fn test_reserved_attribute() {
+        assert!(is_reserved_attribute("priority"));
+        assert!(is_reserved_attribute("SameSite"));
+        assert!(is_reserved_attribute("Max-Age"));
+        assert!(!is_reserved_attribute("comment"));
+        assert!(!is_reserved_attribute("max_age"));
+    }
+}